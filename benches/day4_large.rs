@@ -0,0 +1,55 @@
+use aoc2025::{
+    day4::{FloorPlan, FloorPlanConfig, Strategy},
+    fixtures,
+};
+use criterion::{Criterion, criterion_group, criterion_main};
+
+const SIZE: usize = 10_000;
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let config = FloorPlanConfig::default();
+    let bitboard = FloorPlanConfig {
+        strategy: Strategy::Bitboard,
+        ..config
+    };
+    let mut group = c.benchmark_group("day4_large");
+    group.sample_size(10);
+
+    for density in [60, 75] {
+        let data = fixtures::floor_plan(SIZE, SIZE, density, 0x2025);
+        assert_eq!(
+            FloorPlan::removable_paper_rolls(&data, &config),
+            FloorPlan::removable_paper_rolls_rescanning(&data, &config)
+        );
+        assert_eq!(
+            FloorPlan::removable_paper_rolls(&data, &config),
+            FloorPlan::removable_paper_rolls(&data, &bitboard)
+        );
+        assert_eq!(
+            FloorPlan::accessible_paper_rolls(&data, &config),
+            FloorPlan::accessible_paper_rolls(&data, &bitboard)
+        );
+
+        group.bench_function(format!("accessible_{density}"), |b| {
+            b.iter(|| FloorPlan::accessible_paper_rolls(&data, &config))
+        });
+        group.bench_function(format!("accessible_bitboard_{density}"), |b| {
+            b.iter(|| FloorPlan::accessible_paper_rolls(&data, &bitboard))
+        });
+
+        group.bench_function(format!("worklist_{density}"), |b| {
+            b.iter(|| FloorPlan::removable_paper_rolls(&data, &config))
+        });
+        group.bench_function(format!("bitboard_{density}"), |b| {
+            b.iter(|| FloorPlan::removable_paper_rolls(&data, &bitboard))
+        });
+        group.bench_function(format!("rescanning_{density}"), |b| {
+            b.iter(|| FloorPlan::removable_paper_rolls_rescanning(&data, &config))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -3,10 +3,14 @@ use criterion::{Criterion, criterion_group, criterion_main};
 fn criterion_benchmark(c: &mut Criterion) {
     let data = std::fs::read_to_string("inputs/day5.txt").unwrap();
     c.bench_function("day5_part1", |b| {
-        b.iter(|| aoc2025::day5::Inventory::spoiled_items(&data))
+        b.iter(|| {
+            aoc2025::day5::Inventory::report(&data)
+                .fresh_listed_items()
+                .len()
+        })
     });
     c.bench_function("day5_part2", |b| {
-        b.iter(|| aoc2025::day5::Inventory::fresh_items(&data))
+        b.iter(|| aoc2025::day5::Inventory::report(&data).fresh_id_count())
     });
 }
 
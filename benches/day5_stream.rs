@@ -0,0 +1,43 @@
+use aoc2025::{day5::Inventory, fixtures, range_set::RangeSet};
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+
+const RANGES: usize = 100_000;
+const ITEMS: usize = 1_000_000;
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let (ranges, items) = fixtures::inventory(RANGES, ITEMS, 0x2025);
+    let index = Inventory::range_index(&ranges);
+    let mut serialized = vec![];
+    index.write_to(&mut serialized).unwrap();
+
+    let mut group = c.benchmark_group("day5_stream");
+
+    group.throughput(Throughput::Elements(RANGES as u64));
+    group.bench_function("build_index", |b| {
+        b.iter(|| Inventory::range_index(&ranges))
+    });
+    group.bench_function("write_index", |b| {
+        b.iter(|| {
+            let mut buffer = Vec::with_capacity(serialized.len());
+            index.write_to(&mut buffer).unwrap();
+            buffer
+        })
+    });
+    group.bench_function("read_index", |b| {
+        b.iter(|| RangeSet::read_from(serialized.as_slice()).unwrap())
+    });
+
+    group.throughput(Throughput::Elements(ITEMS as u64));
+    group.bench_function("check_items", |b| {
+        b.iter(|| {
+            Inventory::check_items(&index, items.as_bytes())
+                .filter(|checked| checked.as_ref().is_ok_and(|(_, fresh)| *fresh))
+                .count()
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
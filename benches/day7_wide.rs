@@ -0,0 +1,41 @@
+use aoc2025::{day7::TachyonManifold, fixtures};
+use criterion::{Criterion, criterion_group, criterion_main};
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("day7_wide");
+    group.sample_size(10);
+
+    let sparse = TachyonManifold::new(&fixtures::sparse_manifold(
+        100_000, 2_000, 50_000, 2, 0x2025,
+    ))
+    .unwrap();
+    assert_eq!(sparse.count_splits(), sparse.count_splits_hashed());
+    assert_eq!(sparse.count_timelines(), sparse.count_timelines_hashed());
+
+    group.bench_function("sparse_splits_bitset", |b| b.iter(|| sparse.count_splits()));
+    group.bench_function("sparse_splits_hashed", |b| {
+        b.iter(|| sparse.count_splits_hashed())
+    });
+    group.bench_function("sparse_timelines_vec", |b| {
+        b.iter(|| sparse.count_timelines())
+    });
+    group.bench_function("sparse_timelines_hashed", |b| {
+        b.iter(|| sparse.count_timelines_hashed())
+    });
+
+    // Timelines overflow on a full triangle, only splits are compared
+    let triangle = TachyonManifold::new(&fixtures::triangle_manifold(4_000)).unwrap();
+    assert_eq!(triangle.count_splits(), triangle.count_splits_hashed());
+
+    group.bench_function("triangle_splits_bitset", |b| {
+        b.iter(|| triangle.count_splits())
+    });
+    group.bench_function("triangle_splits_hashed", |b| {
+        b.iter(|| triangle.count_splits_hashed())
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,259 @@
+use std::{
+    cmp::Ordering,
+    fmt::Display,
+    ops::{Add, AddAssign, Mul},
+};
+
+/// Largest power of 10 that fits in an `u64`, used to print in base 10
+const DECIMAL_BASE: u64 = 10_000_000_000_000_000_000;
+
+/// Unsigned integer of arbitrary precision
+///
+/// Stored as little endian `u64` limbs, without leading zero limbs, so zero has no limbs.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct BigUint {
+    limbs: Vec<u64>,
+}
+
+impl BigUint {
+    pub fn zero() -> Self {
+        Self::default()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    /// Number of bits needed to write the number, 0 for zero
+    pub fn bits(&self) -> u64 {
+        self.limbs.last().map_or(0, |last| {
+            (self.limbs.len() as u64 - 1) * 64 + u64::from(u64::BITS - last.leading_zeros())
+        })
+    }
+
+    pub fn to_u64(&self) -> Option<u64> {
+        match self.limbs[..] {
+            [] => Some(0),
+            [limb] => Some(limb),
+            _ => None,
+        }
+    }
+
+    pub fn checked_sub(&self, rhs: u64) -> Option<Self> {
+        let mut limbs = self.limbs.clone();
+        let mut borrow = rhs;
+        for limb in limbs.iter_mut() {
+            if borrow == 0 {
+                break;
+            }
+            let (difference, underflow) = limb.overflowing_sub(borrow);
+            *limb = difference;
+            borrow = u64::from(underflow);
+        }
+        if borrow != 0 || (limbs.is_empty() && rhs != 0) {
+            return None;
+        }
+        Some(Self::normalized(limbs))
+    }
+
+    /// Quotient and remainder of the division by `rhs`, `None` if `rhs` is 0
+    pub fn checked_div_rem(&self, rhs: u64) -> Option<(Self, u64)> {
+        if rhs == 0 {
+            return None;
+        }
+        let mut limbs = self.limbs.clone();
+        let mut remainder = 0u128;
+        for limb in limbs.iter_mut().rev() {
+            let current = (remainder << 64) | u128::from(*limb);
+            *limb = (current / u128::from(rhs)) as u64;
+            remainder = current % u128::from(rhs);
+        }
+        Some((Self::normalized(limbs), remainder as u64))
+    }
+
+    /// Raises to the power `exponent` by squaring
+    pub fn pow(&self, mut exponent: u32) -> Self {
+        let mut result = Self::from(1u64);
+        let mut base = self.clone();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = &result * &base;
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+
+    fn normalized(mut limbs: Vec<u64>) -> Self {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        Self { limbs }
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(value: u64) -> Self {
+        Self::normalized(vec![value])
+    }
+}
+
+impl From<u128> for BigUint {
+    fn from(value: u128) -> Self {
+        Self::normalized(vec![value as u64, (value >> 64) as u64])
+    }
+}
+
+impl AddAssign<&BigUint> for BigUint {
+    fn add_assign(&mut self, rhs: &BigUint) {
+        if self.limbs.len() < rhs.limbs.len() {
+            self.limbs.resize(rhs.limbs.len(), 0);
+        }
+        let mut carry = false;
+        for (index, limb) in self.limbs.iter_mut().enumerate() {
+            let other = rhs.limbs.get(index).copied().unwrap_or(0);
+            if other == 0 && !carry && index >= rhs.limbs.len() {
+                break;
+            }
+            let (sum, first) = limb.overflowing_add(other);
+            let (sum, second) = sum.overflowing_add(u64::from(carry));
+            *limb = sum;
+            carry = first || second;
+        }
+        if carry {
+            self.limbs.push(1);
+        }
+    }
+}
+
+impl Add<&BigUint> for BigUint {
+    type Output = BigUint;
+
+    fn add(mut self, rhs: &BigUint) -> Self::Output {
+        self += rhs;
+        self
+    }
+}
+
+impl Add<u64> for BigUint {
+    type Output = BigUint;
+
+    fn add(self, rhs: u64) -> Self::Output {
+        self + &BigUint::from(rhs)
+    }
+}
+
+impl Mul<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    /// Schoolbook multiplication, in `O(n * m)`
+    fn mul(self, rhs: &BigUint) -> Self::Output {
+        let mut limbs = vec![0u64; self.limbs.len() + rhs.limbs.len()];
+        for (i, lhs) in self.limbs.iter().enumerate() {
+            let mut carry = 0u128;
+            for (j, rhs) in rhs.limbs.iter().enumerate() {
+                let product =
+                    u128::from(*lhs) * u128::from(*rhs) + u128::from(limbs[i + j]) + carry;
+                limbs[i + j] = product as u64;
+                carry = product >> 64;
+            }
+            limbs[i + rhs.limbs.len()] = carry as u64;
+        }
+        BigUint::normalized(limbs)
+    }
+}
+
+impl Mul<u64> for BigUint {
+    type Output = BigUint;
+
+    fn mul(self, rhs: u64) -> Self::Output {
+        &self * &BigUint::from(rhs)
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for BigUint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Chunks of 19 decimal digits, from the least significant
+        let mut chunks = vec![];
+        let mut rest = self.clone();
+        while !rest.is_zero() {
+            let (quotient, chunk) = rest
+                .checked_div_rem(DECIMAL_BASE)
+                .expect("Base is never zero");
+            chunks.push(chunk);
+            rest = quotient;
+        }
+
+        let Some((first, rest)) = chunks.split_last() else {
+            return write!(f, "0");
+        };
+        write!(f, "{first}")?;
+        for chunk in rest.iter().rev() {
+            write!(f, "{chunk:019}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arithmetic() {
+        let max = BigUint::from(u64::MAX);
+        assert_eq!(max.clone() + 1, BigUint::from(1u128 << 64));
+        assert_eq!((max.clone() + 1).checked_sub(1), Some(max.clone()));
+        assert_eq!(BigUint::from(5u64).checked_sub(6), None);
+        assert_eq!(BigUint::zero().checked_sub(1), None);
+        assert_eq!(BigUint::zero().checked_sub(0), Some(BigUint::zero()));
+        assert_eq!(
+            max.clone() * u64::MAX,
+            BigUint::from(u128::from(u64::MAX) * u128::from(u64::MAX))
+        );
+        assert_eq!(
+            BigUint::from(u128::MAX).checked_div_rem(u64::MAX),
+            Some((BigUint::from((1u128 << 64) + 1), 0))
+        );
+        assert_eq!(max.checked_div_rem(0), None);
+        assert_eq!(BigUint::from(2u64).pow(200).bits(), 201);
+        assert_eq!(BigUint::from(7u64).pow(0), BigUint::from(1u64));
+        assert!(BigUint::from(2u64).pow(64) > max);
+        assert!(BigUint::from(3u64) < BigUint::from(4u64));
+        assert_eq!(BigUint::from(1u128 << 64).to_u64(), None);
+        assert_eq!(BigUint::from(42u64).to_u64(), Some(42));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(BigUint::zero().to_string(), "0");
+        assert_eq!(BigUint::from(u64::MAX).to_string(), u64::MAX.to_string());
+        assert_eq!(BigUint::from(u128::MAX).to_string(), u128::MAX.to_string());
+        assert_eq!(
+            BigUint::from(10u64).pow(40).to_string(),
+            format!("1{}", "0".repeat(40))
+        );
+        assert_eq!(
+            BigUint::from(2u64).pow(256).to_string(),
+            "115792089237316195423570985008687907853269984665640564039457584007913129639936"
+        );
+    }
+}
@@ -1,16 +1,14 @@
-use std::{fmt::Debug, num::ParseIntError};
+use std::num::ParseIntError;
+
+use crate::grid::Grid;
 
 #[derive(Debug)]
 pub struct UnderTheChrismasTree {
     #[expect(dead_code, reason = "Day 12 can be cheated, basically")]
-    present_shapes: [Grid; 6],
+    present_shapes: [Grid<bool>; 6],
     problems: Vec<Problem>,
 }
 
-struct Grid {
-    rows: [u64; 64],
-}
-
 #[derive(Debug)]
 struct Problem {
     x: usize,
@@ -34,17 +32,14 @@ impl UnderTheChrismasTree {
                 debug_assert_eq!(header, format!("{i}:"));
                 debug_assert!(empty.is_empty());
 
-                let mut shape = [0; 64];
-
-                for (i, row) in [l1, l2, l3].into_iter().enumerate() {
-                    for (j, c) in row.chars().enumerate() {
-                        if c == '#' {
-                            shape[i] |= 1 << (2 - j);
-                        }
+                // Shape lines may have their trailing `.` trimmed
+                let mut shape = Grid::new(3, 3, false);
+                for (row, line) in [l1, l2, l3].into_iter().enumerate() {
+                    for (column, c) in line.bytes().enumerate() {
+                        shape[(row, column)] = c == b'#';
                     }
                 }
-
-                Grid { rows: shape }
+                shape
             })
             .collect::<Vec<_>>();
 
@@ -99,20 +94,10 @@ impl UnderTheChrismasTree {
     }
 }
 
-impl Debug for Grid {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for row in self.rows {
-            writeln!(f, "{row:064b}")?;
-        }
-        Ok(())
-    }
-}
-
 #[cfg(test)]
 mod tests {
-    // use super::*;
+    use super::*;
 
-    #[expect(dead_code, reason = "Day 12 can be cheated, basically")]
     const INPUT: &str = "0:
 ###
 ##.
@@ -148,6 +133,20 @@ mod tests {
 12x5: 1 0 1 0 3 2
 ";
 
+    #[test]
+    fn test_parse_ragged_shapes() {
+        let ragged = INPUT
+            .lines()
+            .map(|line| line.trim_end_matches('.').to_string() + "\n")
+            .collect::<String>();
+        let tree = UnderTheChrismasTree::parse(&ragged);
+        assert_eq!(tree.problems.len(), 3);
+        assert_eq!(
+            tree.valid_placements(),
+            UnderTheChrismasTree::parse(INPUT).valid_placements()
+        );
+    }
+
     #[test]
     fn test_shape_placements() {
         // Grug solution does not work for test input
@@ -1,59 +1,293 @@
-use std::ops::{Add, Sub};
+use std::fmt::Display;
 
+/// Selects the batteries to turn on in banks of batteries
+///
+/// An empty bank has a joltage of 0 in every mode, whatever the number of
+/// batteries to turn on. Any other bank that is too short for the selection
+/// fails with [`PowerBankError::NotEnoughBatteries`].
 pub struct PowerBank;
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PowerBankError {
+    /// Radix must be in `2..=36`
+    InvalidRadix(u32),
+    /// Battery at `position` of the bank is not a digit of the radix
+    InvalidBattery { position: usize, battery: char },
+    /// Bank has fewer batteries than the ones to turn on, once the gaps
+    /// and forbidden positions are taken into account
+    NotEnoughBatteries,
+    /// Joltage does not fit in an `u64`
+    Overflow,
+}
+
 impl PowerBank {
     /// Takes in multiple banks separated by `\n`
-    pub fn best_joltage_multiple_banks(storage: &str, batteries_to_turn_on: usize) -> u64 {
-        storage
-            .lines()
-            .map(|line| Self::best_joltage(line, batteries_to_turn_on))
-            .sum()
+    pub fn best_joltage_multiple_banks(
+        storage: &str,
+        batteries_to_turn_on: usize,
+    ) -> Result<u64, PowerBankError> {
+        Self::best_joltage_multiple_banks_in_radix(storage, batteries_to_turn_on, 10)
+    }
+
+    /// Takes in multiple banks separated by `\n`, where each battery
+    /// is a digit of `radix`
+    pub fn best_joltage_multiple_banks_in_radix(
+        storage: &str,
+        batteries_to_turn_on: usize,
+        radix: u32,
+    ) -> Result<u64, PowerBankError> {
+        storage.lines().try_fold(0u64, |sum, line| {
+            Self::best_joltage_in_radix(line, batteries_to_turn_on, radix)?
+                .checked_add(sum)
+                .ok_or(PowerBankError::Overflow)
+        })
+    }
+
+    /// Takes in a single bank, where each battery is a digit of `radix`,
+    /// and computes the joltage in that same `radix`
+    pub fn best_joltage_in_radix(
+        bank: &str,
+        batteries_to_turn_on: usize,
+        radix: u32,
+    ) -> Result<u64, PowerBankError> {
+        let batteries = Self::read_batteries(bank, radix)?;
+        if batteries.is_empty() {
+            return Ok(0);
+        }
+        Self::selected_joltage(
+            &batteries,
+            batteries_to_turn_on,
+            0,
+            radix,
+            |battery, best| battery > best,
+        )
     }
 
     /// Takes in a single bank
-    pub fn best_joltage(bank: &str, batteries_to_turn_on: usize) -> u64 {
-        if bank.is_empty() {
-            0
-        } else {
-            Self::joltage_dynamic_programing(bank, batteries_to_turn_on)
+    pub fn best_joltage(bank: &str, batteries_to_turn_on: usize) -> Result<u64, PowerBankError> {
+        Self::best_joltage_in_radix(bank, batteries_to_turn_on, 10)
+    }
+
+    /// Takes in a single bank, turning on the batteries that produce the lowest joltage
+    pub fn worst_joltage(bank: &str, batteries_to_turn_on: usize) -> Result<u64, PowerBankError> {
+        Self::worst_joltage_in_radix(bank, batteries_to_turn_on, 10)
+    }
+
+    /// Same as [`PowerBank::worst_joltage`], where each battery is a digit of `radix`
+    pub fn worst_joltage_in_radix(
+        bank: &str,
+        batteries_to_turn_on: usize,
+        radix: u32,
+    ) -> Result<u64, PowerBankError> {
+        let batteries = Self::read_batteries(bank, radix)?;
+        if batteries.is_empty() {
+            return Ok(0);
         }
+        Self::selected_joltage(
+            &batteries,
+            batteries_to_turn_on,
+            0,
+            radix,
+            |battery, best| battery < best,
+        )
+    }
+
+    /// Takes in a single bank, leaving at least `minimum_gap` batteries turned off
+    /// between any two batteries that are turned on
+    ///
+    /// Fails with [`PowerBankError::NotEnoughBatteries`] if the bank can't fit
+    /// all batteries with the gap.
+    pub fn best_joltage_with_gap(
+        bank: &str,
+        batteries_to_turn_on: usize,
+        minimum_gap: usize,
+    ) -> Result<u64, PowerBankError> {
+        Self::best_joltage_with_gap_in_radix(bank, batteries_to_turn_on, minimum_gap, 10)
     }
 
-    fn joltage_dynamic_programing(bank: &str, batteries_to_turn_on: usize) -> u64 {
-        if batteries_to_turn_on == 0 {
-            return 0;
+    /// Same as [`PowerBank::best_joltage_with_gap`], where each battery is a digit of `radix`
+    pub fn best_joltage_with_gap_in_radix(
+        bank: &str,
+        batteries_to_turn_on: usize,
+        minimum_gap: usize,
+        radix: u32,
+    ) -> Result<u64, PowerBankError> {
+        let batteries = Self::read_batteries(bank, radix)?;
+        if batteries.is_empty() {
+            return Ok(0);
         }
+        Self::selected_joltage(
+            &batteries,
+            batteries_to_turn_on,
+            minimum_gap,
+            radix,
+            |battery, best| battery > best,
+        )
+    }
+
+    /// Takes in a single bank, never turning on the batteries at the `forbidden` positions
+    pub fn best_joltage_with_forbidden(
+        bank: &str,
+        batteries_to_turn_on: usize,
+        forbidden: &[usize],
+    ) -> Result<u64, PowerBankError> {
+        Self::best_joltage_with_forbidden_in_radix(bank, batteries_to_turn_on, forbidden, 10)
+    }
+
+    /// Same as [`PowerBank::best_joltage_with_forbidden`], where each battery is a digit of `radix`
+    pub fn best_joltage_with_forbidden_in_radix(
+        bank: &str,
+        batteries_to_turn_on: usize,
+        forbidden: &[usize],
+        radix: u32,
+    ) -> Result<u64, PowerBankError> {
+        let batteries = Self::read_batteries(bank, radix)?;
+        if batteries.is_empty() {
+            return Ok(0);
+        }
+        let batteries = batteries
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, battery)| (!forbidden.contains(&i)).then_some(battery))
+            .collect::<Vec<_>>();
+        Self::selected_joltage(
+            &batteries,
+            batteries_to_turn_on,
+            0,
+            radix,
+            |battery, best| battery > best,
+        )
+    }
 
-        let relevant_batteries = &bank[..=bank.len().sub(batteries_to_turn_on)];
-        let (max, index) =
-            relevant_batteries
-                .chars()
+    /// Takes in a single bank, turning on the batteries whose joltages
+    /// add up to the largest sum
+    pub fn best_joltage_sum(
+        bank: &str,
+        batteries_to_turn_on: usize,
+    ) -> Result<u64, PowerBankError> {
+        Self::best_joltage_sum_in_radix(bank, batteries_to_turn_on, 10)
+    }
+
+    /// Same as [`PowerBank::best_joltage_sum`], where each battery is a digit of `radix`
+    pub fn best_joltage_sum_in_radix(
+        bank: &str,
+        batteries_to_turn_on: usize,
+        radix: u32,
+    ) -> Result<u64, PowerBankError> {
+        let mut batteries = Self::read_batteries(bank, radix)?;
+        if batteries.is_empty() {
+            return Ok(0);
+        }
+        if batteries.len() < batteries_to_turn_on {
+            return Err(PowerBankError::NotEnoughBatteries);
+        }
+        batteries.sort_unstable_by(|l, r| r.cmp(l));
+        Ok(batteries[..batteries_to_turn_on].iter().sum())
+    }
+
+    fn read_batteries(bank: &str, radix: u32) -> Result<Vec<u64>, PowerBankError> {
+        if !(2..=36).contains(&radix) {
+            return Err(PowerBankError::InvalidRadix(radix));
+        }
+        bank.chars()
+            .enumerate()
+            .map(|(position, battery)| {
+                battery
+                    .to_digit(radix)
+                    .map(u64::from)
+                    .ok_or(PowerBankError::InvalidBattery { position, battery })
+            })
+            .collect()
+    }
+
+    fn selected_joltage(
+        batteries: &[u64],
+        batteries_to_turn_on: usize,
+        minimum_gap: usize,
+        radix: u32,
+        prefers: fn(u64, u64) -> bool,
+    ) -> Result<u64, PowerBankError> {
+        let selected =
+            Self::select_batteries(batteries, batteries_to_turn_on, minimum_gap, prefers)
+                .ok_or(PowerBankError::NotEnoughBatteries)?;
+        Self::joltage(&selected, radix)
+    }
+
+    fn joltage(batteries: &[u64], radix: u32) -> Result<u64, PowerBankError> {
+        batteries.iter().try_fold(0u64, |joltage, battery| {
+            joltage
+                .checked_mul(u64::from(radix))
+                .and_then(|joltage| joltage.checked_add(*battery))
+                .ok_or(PowerBankError::Overflow)
+        })
+    }
+
+    /// Greedily picks, from left to right, the battery preferred by `prefers`
+    /// among the ones that still leave room for the remaining batteries.
+    /// Ties go to the leftmost battery, as it leaves the most room for the next picks.
+    fn select_batteries(
+        batteries: &[u64],
+        batteries_to_turn_on: usize,
+        minimum_gap: usize,
+        prefers: fn(u64, u64) -> bool,
+    ) -> Option<Vec<u64>> {
+        let stride = minimum_gap.checked_add(1)?;
+        let mut start = 0;
+        let mut selected = Vec::with_capacity(batteries_to_turn_on);
+
+        for remaining in (0..batteries_to_turn_on).rev() {
+            let last = batteries
+                .len()
+                .checked_sub(remaining.checked_mul(stride)?.checked_add(1)?)?;
+            let (index, battery) = batteries
+                .get(start..=last)?
+                .iter()
+                .copied()
                 .enumerate()
-                .fold(
-                    ('0', 0),
-                    |(max, index), (i, char)| if char > max { (char, i) } else { (max, index) },
-                );
-        let max = max.to_digit(10).map(u64::from).expect("Must be a digit.");
-        let next =
-            Self::joltage_dynamic_programing(&bank[index.add(1)..], batteries_to_turn_on - 1);
-        let multiplier =
-            10u64.pow(u32::try_from(batteries_to_turn_on - 1).expect("Should never overflow"));
-
-        max * multiplier + next
+                .reduce(|(best_index, best), (index, battery)| {
+                    if prefers(battery, best) {
+                        (index, battery)
+                    } else {
+                        (best_index, best)
+                    }
+                })?;
+
+            selected.push(battery);
+            start += index + stride;
+        }
+
+        Some(selected)
+    }
+}
+
+impl Display for PowerBankError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidRadix(radix) => write!(f, "Radix {radix} is not in 2..=36."),
+            Self::InvalidBattery { position, battery } => {
+                write!(
+                    f,
+                    "Battery '{battery}' at position {position} is not a digit."
+                )
+            }
+            Self::NotEnoughBatteries => write!(f, "Bank does not have enough batteries."),
+            Self::Overflow => write!(f, "Joltage does not fit in an u64."),
+        }
     }
 }
 
+impl std::error::Error for PowerBankError {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_banks() {
-        assert_eq!(PowerBank::best_joltage("987654321111111", 2), 98);
-        assert_eq!(PowerBank::best_joltage("811111111111119", 2), 89);
-        assert_eq!(PowerBank::best_joltage("234234234234278", 2), 78);
-        assert_eq!(PowerBank::best_joltage("818181911112111", 2), 92);
+        assert_eq!(PowerBank::best_joltage("987654321111111", 2), Ok(98));
+        assert_eq!(PowerBank::best_joltage("811111111111119", 2), Ok(89));
+        assert_eq!(PowerBank::best_joltage("234234234234278", 2), Ok(78));
+        assert_eq!(PowerBank::best_joltage("818181911112111", 2), Ok(92));
     }
 
     #[test]
@@ -62,7 +296,7 @@ mod tests {
 811111111111119
 234234234234278
 818181911112111";
-        assert_eq!(PowerBank::best_joltage_multiple_banks(input, 2), 357);
+        assert_eq!(PowerBank::best_joltage_multiple_banks(input, 2), Ok(357));
     }
 
     #[test]
@@ -73,7 +307,227 @@ mod tests {
 818181911112111";
         assert_eq!(
             PowerBank::best_joltage_multiple_banks(input, 12),
-            3121910778619
+            Ok(3121910778619)
+        );
+    }
+
+    #[test]
+    fn test_worst_joltage() {
+        assert_eq!(PowerBank::worst_joltage("987654321111111", 2), Ok(11));
+        assert_eq!(PowerBank::worst_joltage("811111111111119", 2), Ok(11));
+        assert_eq!(PowerBank::worst_joltage("234234234234278", 2), Ok(22));
+        assert_eq!(PowerBank::worst_joltage("818181911112111", 3), Ok(111));
+        assert_eq!(
+            PowerBank::worst_joltage("987654321111111", 12),
+            Ok(654321111111)
+        );
+        assert_eq!(
+            PowerBank::worst_joltage("12", 3),
+            Err(PowerBankError::NotEnoughBatteries)
+        );
+    }
+
+    #[test]
+    fn test_best_joltage_with_gap() {
+        assert_eq!(
+            PowerBank::best_joltage_with_gap("987654321111111", 2, 0),
+            Ok(98)
+        );
+        assert_eq!(
+            PowerBank::best_joltage_with_gap("987654321111111", 2, 1),
+            Ok(97)
+        );
+        assert_eq!(
+            PowerBank::best_joltage_with_gap("818181911112111", 2, 3),
+            Ok(92)
+        );
+        assert_eq!(
+            PowerBank::best_joltage_with_gap("818181911112111", 3, 2),
+            Ok(921)
+        );
+        assert_eq!(PowerBank::best_joltage_with_gap("12345", 3, 1), Ok(135));
+        assert_eq!(
+            PowerBank::best_joltage_with_gap("12345", 3, 2),
+            Err(PowerBankError::NotEnoughBatteries)
+        );
+    }
+
+    #[test]
+    fn test_best_joltage_with_forbidden() {
+        assert_eq!(
+            PowerBank::best_joltage_with_forbidden("987654321111111", 2, &[]),
+            Ok(98)
+        );
+        assert_eq!(
+            PowerBank::best_joltage_with_forbidden("987654321111111", 2, &[0]),
+            Ok(87)
+        );
+        assert_eq!(
+            PowerBank::best_joltage_with_forbidden("811111111111119", 2, &[14]),
+            Ok(81)
+        );
+        assert_eq!(
+            PowerBank::best_joltage_with_forbidden("123", 2, &[0, 2]),
+            Err(PowerBankError::NotEnoughBatteries)
+        );
+    }
+
+    #[test]
+    fn test_best_joltage_sum() {
+        assert_eq!(PowerBank::best_joltage_sum("987654321111111", 2), Ok(17));
+        assert_eq!(PowerBank::best_joltage_sum("811111111111119", 2), Ok(17));
+        assert_eq!(PowerBank::best_joltage_sum("818181911112111", 4), Ok(33));
+        assert_eq!(
+            PowerBank::best_joltage_sum("12", 3),
+            Err(PowerBankError::NotEnoughBatteries)
+        );
+    }
+
+    #[test]
+    fn test_empty_bank() {
+        assert_eq!(PowerBank::best_joltage("", 2), Ok(0));
+        assert_eq!(PowerBank::worst_joltage("", 2), Ok(0));
+        assert_eq!(PowerBank::best_joltage_with_gap("", 2, 3), Ok(0));
+        assert_eq!(PowerBank::best_joltage_with_forbidden("", 2, &[0]), Ok(0));
+        assert_eq!(PowerBank::best_joltage_sum("", 2), Ok(0));
+        assert_eq!(
+            PowerBank::best_joltage_multiple_banks("98\n\n12", 2),
+            Ok(110)
+        );
+        assert_eq!(
+            PowerBank::best_joltage("1", 2),
+            Err(PowerBankError::NotEnoughBatteries)
+        );
+        assert_eq!(
+            PowerBank::best_joltage_with_forbidden("9", 1, &[0]),
+            Err(PowerBankError::NotEnoughBatteries)
+        );
+    }
+
+    #[test]
+    fn test_banks_in_radix() {
+        assert_eq!(
+            PowerBank::best_joltage_in_radix("987654321111111", 2, 10),
+            Ok(98)
+        );
+        assert_eq!(PowerBank::best_joltage_in_radix("1f3a9B", 2, 16), Ok(0xfb));
+        assert_eq!(PowerBank::best_joltage_in_radix("1f3a9B", 3, 16), Ok(0xfab));
+        assert_eq!(PowerBank::best_joltage_in_radix("0110101", 3, 2), Ok(0b111));
+        assert_eq!(
+            PowerBank::best_joltage_in_radix("zay", 2, 36),
+            Ok(35 * 36 + 34)
+        );
+        assert_eq!(PowerBank::best_joltage_in_radix("", 2, 16), Ok(0));
+    }
+
+    #[test]
+    fn test_multiple_banks_in_radix() {
+        let input = "987654321111111
+811111111111119
+234234234234278
+818181911112111";
+        assert_eq!(
+            PowerBank::best_joltage_multiple_banks_in_radix(input, 12, 10),
+            Ok(3121910778619)
+        );
+        assert_eq!(
+            PowerBank::best_joltage_multiple_banks_in_radix("c0ffee\ndeadbeef", 4, 16),
+            Ok(0xffee + 0xeeef)
+        );
+    }
+
+    #[test]
+    fn test_modes_in_radix() {
+        assert_eq!(PowerBank::worst_joltage_in_radix("1f3a9B", 2, 16), Ok(0x13));
+        assert_eq!(
+            PowerBank::worst_joltage_in_radix("0110101", 3, 2),
+            Ok(0b000)
+        );
+        assert_eq!(
+            PowerBank::best_joltage_with_gap_in_radix("1f3a9B", 2, 1, 16),
+            Ok(0xfb)
+        );
+        assert_eq!(
+            PowerBank::best_joltage_with_gap_in_radix("1f3a9B", 3, 1, 16),
+            Ok(0xfab)
+        );
+        assert_eq!(
+            PowerBank::best_joltage_with_forbidden_in_radix("1f3a9B", 2, &[1], 16),
+            Ok(0xab)
+        );
+        assert_eq!(
+            PowerBank::best_joltage_sum_in_radix("1f3a9B", 2, 16),
+            Ok(26)
+        );
+        assert_eq!(
+            PowerBank::worst_joltage_in_radix("12g4", 2, 16),
+            Err(PowerBankError::InvalidBattery {
+                position: 2,
+                battery: 'g'
+            })
+        );
+        assert_eq!(
+            PowerBank::best_joltage_sum_in_radix("", 2, 37),
+            Err(PowerBankError::InvalidRadix(37))
+        );
+        assert_eq!(
+            PowerBank::worst_joltage_in_radix("ffffffffffffffffff", 17, 16),
+            Err(PowerBankError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_banks_in_radix_errors() {
+        assert_eq!(
+            PowerBank::best_joltage_in_radix("12g4", 2, 16),
+            Err(PowerBankError::InvalidBattery {
+                position: 2,
+                battery: 'g'
+            })
+        );
+        assert_eq!(
+            PowerBank::best_joltage_in_radix("1234", 2, 37),
+            Err(PowerBankError::InvalidRadix(37))
+        );
+        assert_eq!(
+            PowerBank::best_joltage_in_radix("ffffffffffffffffff", 17, 16),
+            Err(PowerBankError::Overflow)
+        );
+        assert_eq!(
+            PowerBank::best_joltage_in_radix("1234", 5, 10),
+            Err(PowerBankError::NotEnoughBatteries)
+        );
+        assert_eq!(
+            PowerBank::best_joltage_in_radix("", 2, 1),
+            Err(PowerBankError::InvalidRadix(1))
+        );
+        assert_eq!(
+            PowerBank::best_joltage("12x", 2),
+            Err(PowerBankError::InvalidBattery {
+                position: 2,
+                battery: 'x'
+            })
+        );
+        let bank = "9".repeat(20);
+        assert_eq!(
+            PowerBank::best_joltage(&bank, 19),
+            Ok(9_999_999_999_999_999_999)
+        );
+        assert_eq!(
+            PowerBank::best_joltage(&bank, 20),
+            Err(PowerBankError::Overflow)
+        );
+        assert_eq!(
+            PowerBank::worst_joltage(&bank, 20),
+            Err(PowerBankError::Overflow)
+        );
+        assert_eq!(
+            PowerBank::best_joltage_with_gap(&bank, 20, 0),
+            Err(PowerBankError::Overflow)
+        );
+        assert_eq!(
+            PowerBank::best_joltage_with_forbidden(&bank, 20, &[]),
+            Err(PowerBankError::Overflow)
         );
     }
 }
@@ -0,0 +1,258 @@
+use crate::grid::Grid;
+
+use super::PAPER_ROLL;
+
+/// Floor plan with each row packed into `u64` words, one bit per tile,
+/// where set bits are paper rolls
+///
+/// Bit `i` of word `w` is the tile on column `64 * w + i`.
+pub(super) struct Bitboard {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    words: Vec<u64>,
+}
+
+/// Per tile counters of adjacent paper rolls, stored as bit planes,
+/// where plane `p` holds bit `p` of the counter of each tile of a row
+struct Counters {
+    words_per_row: usize,
+    planes: Vec<u64>,
+}
+
+impl Bitboard {
+    pub(super) fn from_grid(grid: &Grid<u8>) -> Self {
+        let width = grid.width();
+        let words_per_row = width.div_ceil(64);
+        let mut words = vec![0; words_per_row * grid.height()];
+
+        for (row_words, row) in words.chunks_mut(words_per_row.max(1)).zip(grid.rows()) {
+            for (column, _) in row.iter().enumerate().filter(|(_, c)| **c == PAPER_ROLL) {
+                row_words[column / 64] |= 1 << (column % 64);
+            }
+        }
+
+        Self {
+            width,
+            height: grid.height(),
+            words_per_row,
+            words,
+        }
+    }
+
+    pub(super) fn accessible_paper_rolls(
+        &self,
+        offsets: &[(isize, isize)],
+        threshold: u16,
+        wrap_around: bool,
+    ) -> u32 {
+        let mut counters = Counters::new(self.words_per_row, offsets.len(), threshold);
+        let mut scratch = vec![0; self.words_per_row * 2];
+
+        (0..self.height)
+            .map(|row| {
+                self.count_neighbors(row, offsets, wrap_around, &mut counters, &mut scratch);
+                self.row(row)
+                    .iter()
+                    .zip(counters.less_than(threshold))
+                    .map(|(rolls, accessible)| (rolls & accessible).count_ones())
+                    .sum::<u32>()
+            })
+            .sum()
+    }
+
+    /// Removes paper rolls in waves, only revisiting the rows
+    /// that could see a change on their neighbors since the last wave
+    pub(super) fn removable_paper_rolls(
+        &mut self,
+        offsets: &[(isize, isize)],
+        threshold: u16,
+        wrap_around: bool,
+    ) -> u32 {
+        let mut counters = Counters::new(self.words_per_row, offsets.len(), threshold);
+        let mut scratch = vec![0; self.words_per_row * 2];
+
+        let mut row_offsets = offsets.iter().map(|(row, _)| *row).collect::<Vec<_>>();
+        row_offsets.sort_unstable();
+        row_offsets.dedup();
+
+        let mut dirty = vec![true; self.height];
+        let mut removals = vec![];
+        let mut removed = 0;
+
+        loop {
+            for row in (0..self.height).filter(|row| dirty[*row]) {
+                self.count_neighbors(row, offsets, wrap_around, &mut counters, &mut scratch);
+                let removal = self
+                    .row(row)
+                    .iter()
+                    .zip(counters.less_than(threshold))
+                    .map(|(rolls, accessible)| rolls & accessible)
+                    .collect::<Vec<_>>();
+                if removal.iter().any(|word| *word != 0) {
+                    removals.push((row, removal));
+                }
+            }
+
+            if removals.is_empty() {
+                break;
+            }
+
+            dirty.fill(false);
+            for (row, removal) in removals.drain(..) {
+                for (word, removal) in self.row_mut(row).iter_mut().zip(removal) {
+                    removed += removal.count_ones();
+                    *word &= !removal;
+                }
+                // The row at `row - row_offset` sees this row as a neighbor
+                for row_offset in row_offsets.iter() {
+                    if let Some(affected) = self.offset_row(row, -row_offset, wrap_around) {
+                        dirty[affected] = true;
+                    }
+                }
+            }
+        }
+
+        removed
+    }
+
+    /// Fills `counters` with the number of paper rolls adjacent to each tile of `row`
+    fn count_neighbors(
+        &self,
+        row: usize,
+        offsets: &[(isize, isize)],
+        wrap_around: bool,
+        counters: &mut Counters,
+        scratch: &mut [u64],
+    ) {
+        counters.clear();
+        let (shifted, temp) = scratch.split_at_mut(self.words_per_row);
+
+        for (row_offset, column_offset) in offsets {
+            let Some(source) = self.offset_row(row, *row_offset, wrap_around) else {
+                continue;
+            };
+            let source = self.row(source);
+
+            if wrap_around && self.width > 0 {
+                // Rotation made out of a shift to each side
+                let column_offset = column_offset.rem_euclid(self.width as isize);
+                self.shift(source, column_offset, shifted);
+                self.shift(source, column_offset - self.width as isize, temp);
+                for (word, other) in shifted.iter_mut().zip(temp.iter()) {
+                    *word |= other;
+                }
+            } else {
+                self.shift(source, *column_offset, shifted);
+            }
+
+            counters.add(shifted);
+        }
+    }
+
+    /// Fills `out` such that the bit of column `c` is the bit of column `c + amount` of `source`,
+    /// or unset if outside of the row
+    fn shift(&self, source: &[u64], amount: isize, out: &mut [u64]) {
+        let word_shift = amount.unsigned_abs() / 64;
+        let bit_shift = amount.unsigned_abs() % 64;
+        let word = |index: Option<usize>| {
+            index
+                .and_then(|index| source.get(index))
+                .copied()
+                .unwrap_or(0)
+        };
+
+        for (i, out) in out.iter_mut().enumerate() {
+            *out = if amount >= 0 {
+                let low = word(i.checked_add(word_shift));
+                let high = word(i.checked_add(word_shift + 1));
+                if bit_shift == 0 {
+                    low
+                } else {
+                    (low >> bit_shift) | (high << (64 - bit_shift))
+                }
+            } else {
+                let high = word(i.checked_sub(word_shift));
+                let low = word(i.checked_sub(word_shift + 1));
+                if bit_shift == 0 {
+                    high
+                } else {
+                    (high << bit_shift) | (low >> (64 - bit_shift))
+                }
+            };
+        }
+
+        // Bits past the width of the row must stay unset
+        if let Some(last) = out.last_mut()
+            && !self.width.is_multiple_of(64)
+        {
+            *last &= (1 << (self.width % 64)) - 1;
+        }
+    }
+
+    fn offset_row(&self, row: usize, row_offset: isize, wrap_around: bool) -> Option<usize> {
+        if wrap_around {
+            let row_offset = row_offset.rem_euclid(self.height as isize) as usize;
+            Some((row + row_offset) % self.height)
+        } else {
+            row.checked_add_signed(row_offset)
+                .filter(|row| *row < self.height)
+        }
+    }
+
+    fn row(&self, row: usize) -> &[u64] {
+        &self.words[(row * self.words_per_row)..((row + 1) * self.words_per_row)]
+    }
+
+    fn row_mut(&mut self, row: usize) -> &mut [u64] {
+        &mut self.words[(row * self.words_per_row)..((row + 1) * self.words_per_row)]
+    }
+}
+
+impl Counters {
+    /// Creates counters able to hold up to `max(neighbors, threshold)`
+    fn new(words_per_row: usize, neighbors: usize, threshold: u16) -> Self {
+        let max = neighbors.max(usize::from(threshold));
+        let bits = (usize::BITS - max.leading_zeros()) as usize;
+        Self {
+            words_per_row,
+            planes: vec![0; bits * words_per_row],
+        }
+    }
+
+    fn clear(&mut self) {
+        self.planes.fill(0);
+    }
+
+    /// Adds one to the counters of the tiles set on `mask`, rippling carries through the planes
+    fn add(&mut self, mask: &[u64]) {
+        for (word, mask) in mask.iter().enumerate() {
+            let mut carry = *mask;
+            for plane in self.planes.chunks_mut(self.words_per_row) {
+                if carry == 0 {
+                    break;
+                }
+                let sum = plane[word] ^ carry;
+                carry &= plane[word];
+                plane[word] = sum;
+            }
+        }
+    }
+
+    /// Iterates over masks of the tiles whose counters are less than `threshold`
+    fn less_than(&self, threshold: u16) -> impl Iterator<Item = u64> {
+        (0..self.words_per_row).map(move |word| {
+            let mut less = 0;
+            let mut equal = u64::MAX;
+            for (bit, plane) in self.planes.chunks(self.words_per_row).enumerate().rev() {
+                if (u64::from(threshold) >> bit) & 1 == 1 {
+                    less |= equal & !plane[word];
+                    equal &= plane[word];
+                } else {
+                    equal &= !plane[word];
+                }
+            }
+            less
+        })
+    }
+}
@@ -1,35 +1,271 @@
+mod bitboard;
+
+use std::fmt::Display;
+
+use crate::grid::Grid;
+
+use self::bitboard::Bitboard;
+
 const EMPTY_TILE: u8 = b'.';
 const PAPER_ROLL: u8 = b'@';
+/// Tile that can't hold a paper roll, and does not count as one
+const OBSTACLE: u8 = b'#';
+
+/// Floor of the warehouse, whose adjacent paper roll counters are kept
+/// up to date as paper rolls are placed and removed
+///
+/// Always uses [`Strategy::AdjacencyMatrix`].
+#[derive(Debug, Clone)]
+pub struct FloorPlan {
+    grid: Grid<u8>,
+    adjacency_matrix: Grid<u16>,
+    offsets: Vec<(isize, isize)>,
+    config: FloorPlanConfig,
+    /// Number of paper rolls that can currently be accessed
+    accessible: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FloorPlanConfig {
+    /// Tiles considered adjacent to a paper roll
+    pub neighborhood: Neighborhood,
+    /// Paper rolls with less than `threshold` adjacent paper rolls can be accessed
+    pub threshold: u16,
+    /// Whether the edges of the floor wrap around to the opposite side
+    pub wrap_around: bool,
+    /// How adjacent paper rolls are counted
+    pub strategy: Strategy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Strategy {
+    /// Keeps a counter of adjacent paper rolls for each tile
+    #[default]
+    AdjacencyMatrix,
+    /// Packs rows into `u64` words and counts adjacent paper rolls
+    /// for 64 tiles at a time with shifted adds
+    Bitboard,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemovalReport {
+    /// Positions, as `(row, column)`, of the paper rolls removed on each wave
+    waves: Vec<Vec<(usize, usize)>>,
+    final_floor_plan: Grid<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FloorPlanError {
+    /// Floor plan has no tiles
+    Empty,
+    /// Tile is not one of `.`, `@`, `#` or ` `
+    InvalidTile {
+        row: usize,
+        column: usize,
+        tile: char,
+    },
+    /// Neighborhood holds more tiles than an `u16` can count
+    NeighborhoodTooLarge,
+    /// Neighborhood spans `diameter` tiles, more than the width or height of the floor,
+    /// so wrapping around would count some tiles twice, and paper rolls as their own neighbor
+    WrappingNeighborhoodTooWide {
+        diameter: usize,
+        width: usize,
+        height: usize,
+    },
+    /// Position is outside of the floor
+    OutOfBounds { row: usize, column: usize },
+}
 
-pub struct FloorPlan;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Neighborhood {
+    /// Tiles within `radius` in both directions, including diagonals
+    Moore { radius: usize },
+    /// Tiles within a manhattan distance of `radius`
+    VonNeumann { radius: usize },
+}
 
 impl FloorPlan {
-    pub fn accessible_paper_rolls(floor_plan: &[u8]) -> u32 {
-        let grid = Self::gridfy_floor_plan(floor_plan);
+    pub fn new(floor_plan: &[u8], config: FloorPlanConfig) -> Result<Self, FloorPlanError> {
+        let grid = Self::parse(floor_plan)?;
+        let offsets = Self::offsets(&grid, &config)?;
+        let adjacency_matrix = Self::build_adjacency_matrix(&grid, &offsets, config.wrap_around);
+        let accessible = grid
+            .iter()
+            .zip(adjacency_matrix.iter())
+            .filter(|(tile, adjacent)| **tile == PAPER_ROLL && **adjacent < config.threshold)
+            .count() as u32;
 
-        let adjacency_matrix = Self::build_adjacency_matrix(&grid);
+        Ok(Self {
+            grid,
+            adjacency_matrix,
+            offsets,
+            config,
+            accessible,
+        })
+    }
 
-        adjacency_matrix
+    /// Places a paper roll on `position`, returning `false` if the tile is not empty
+    ///
+    /// Fails without changing anything if `position` is outside of the floor.
+    pub fn place_paper_roll(&mut self, position: (usize, usize)) -> Result<bool, FloorPlanError> {
+        if self.tile(position)? != EMPTY_TILE {
+            return Ok(false);
+        }
+
+        self.update_neighbors(position, |adjacent| adjacent + 1);
+        self.grid[position] = PAPER_ROLL;
+        if self.is_accessible(position) {
+            self.accessible += 1;
+        }
+
+        Ok(true)
+    }
+
+    /// Removes the paper roll on `position`, returning `false` if there was none
+    ///
+    /// Fails without changing anything if `position` is outside of the floor.
+    pub fn remove_paper_roll(&mut self, position: (usize, usize)) -> Result<bool, FloorPlanError> {
+        if self.tile(position)? != PAPER_ROLL {
+            return Ok(false);
+        }
+
+        if self.is_accessible(position) {
+            self.accessible -= 1;
+        }
+        self.grid[position] = EMPTY_TILE;
+        self.update_neighbors(position, |adjacent| adjacent - 1);
+
+        Ok(true)
+    }
+
+    fn tile(&self, position: (usize, usize)) -> Result<u8, FloorPlanError> {
+        let (row, column) = position;
+        self.grid
+            .get(position)
+            .copied()
+            .ok_or(FloorPlanError::OutOfBounds { row, column })
+    }
+
+    pub fn is_paper_roll(&self, position: (usize, usize)) -> bool {
+        self.grid.get(position) == Some(&PAPER_ROLL)
+    }
+
+    pub fn is_accessible(&self, position: (usize, usize)) -> bool {
+        self.is_paper_roll(position) && self.adjacency_matrix[position] < self.config.threshold
+    }
+
+    /// Number of paper rolls that can currently be accessed
+    pub fn accessible(&self) -> u32 {
+        self.accessible
+    }
+
+    /// Number of paper rolls that could be removed by repeatedly removing
+    /// accessible paper rolls, without modifying the floor
+    pub fn removable_by_cascade(&self) -> u32 {
+        let mut grid = self.grid.clone();
+        let mut adjacency_matrix = self.adjacency_matrix.clone();
+        Self::remove_paper_rolls(
+            &mut grid,
+            &mut adjacency_matrix,
+            &self.offsets,
+            &self.config,
+            |_| (),
+        )
+    }
+
+    pub fn accessible_paper_rolls(
+        floor_plan: &[u8],
+        config: &FloorPlanConfig,
+    ) -> Result<u32, FloorPlanError> {
+        let grid = Self::parse(floor_plan)?;
+        let offsets = Self::offsets(&grid, config)?;
+
+        if config.strategy == Strategy::Bitboard {
+            return Ok(Bitboard::from_grid(&grid).accessible_paper_rolls(
+                &offsets,
+                config.threshold,
+                config.wrap_around,
+            ));
+        }
+
+        let adjacency_matrix = Self::build_adjacency_matrix(&grid, &offsets, config.wrap_around);
+
+        Ok(adjacency_matrix
             .iter()
-            .flatten()
-            .zip(grid.iter().flat_map(|row| row.iter()))
+            .zip(grid.iter())
             .filter_map(|(adjacent, tile)| {
-                if *tile == PAPER_ROLL && *adjacent < 4 {
+                if *tile == PAPER_ROLL && *adjacent < config.threshold {
                     Some(1)
                 } else {
                     None
                 }
             })
-            .sum()
+            .sum())
+    }
+
+    pub fn removable_paper_rolls(
+        floor_plan: &[u8],
+        config: &FloorPlanConfig,
+    ) -> Result<u32, FloorPlanError> {
+        let mut grid = Self::parse(floor_plan)?;
+        let offsets = Self::offsets(&grid, config)?;
+
+        Ok(match config.strategy {
+            Strategy::AdjacencyMatrix => {
+                let mut adjacency_matrix =
+                    Self::build_adjacency_matrix(&grid, &offsets, config.wrap_around);
+                Self::remove_paper_rolls(&mut grid, &mut adjacency_matrix, &offsets, config, |_| ())
+            }
+            Strategy::Bitboard => Bitboard::from_grid(&grid).removable_paper_rolls(
+                &offsets,
+                config.threshold,
+                config.wrap_around,
+            ),
+        })
     }
 
-    pub fn removable_paper_rolls(floor_plan: &[u8]) -> u32 {
-        let mut grid = Self::gridfy_floor_plan(floor_plan);
+    /// Same as [`FloorPlan::removable_paper_rolls`], but keeps track of
+    /// the paper rolls removed on each wave and of the floor left after the removals
+    ///
+    /// Always uses [`Strategy::AdjacencyMatrix`].
+    pub fn removal_report(
+        floor_plan: &[u8],
+        config: &FloorPlanConfig,
+    ) -> Result<RemovalReport, FloorPlanError> {
+        let mut grid = Self::parse(floor_plan)?;
+        let offsets = Self::offsets(&grid, config)?;
+        let mut adjacency_matrix =
+            Self::build_adjacency_matrix(&grid, &offsets, config.wrap_around);
 
-        let rows = grid.len();
-        let columns = grid[0].len();
+        let mut waves = vec![];
+        Self::remove_paper_rolls(&mut grid, &mut adjacency_matrix, &offsets, config, |wave| {
+            let mut wave = wave.to_vec();
+            wave.sort_unstable();
+            waves.push(wave);
+        });
 
-        let mut adjacency_matrix = Self::build_adjacency_matrix(&grid);
+        Ok(RemovalReport {
+            waves,
+            final_floor_plan: grid,
+        })
+    }
+
+    /// Same as [`FloorPlan::removable_paper_rolls`], but rescans the whole floor
+    /// until no more paper rolls can be removed
+    ///
+    /// Kept as a reference implementation for the tests and the benchmarks,
+    /// always uses [`Strategy::AdjacencyMatrix`].
+    #[cfg(any(test, feature = "reference"))]
+    pub fn removable_paper_rolls_rescanning(
+        floor_plan: &[u8],
+        config: &FloorPlanConfig,
+    ) -> Result<u32, FloorPlanError> {
+        let mut grid = Self::parse(floor_plan)?;
+        let offsets = Self::offsets(&grid, config)?;
+
+        let mut adjacency_matrix =
+            Self::build_adjacency_matrix(&grid, &offsets, config.wrap_around);
         let mut adjacency_matrix_b = adjacency_matrix.clone();
 
         let mut removed = 0;
@@ -37,101 +273,283 @@ impl FloorPlan {
 
         while updated {
             updated = false;
-            for (row, row_b) in adjacency_matrix.iter_mut().zip(adjacency_matrix_b.iter()) {
-                row.copy_from_slice(row_b);
-            }
+            adjacency_matrix.clone_from(&adjacency_matrix_b);
 
-            for (row, column, tile, adjacency) in
-                grid.iter_mut()
-                    .zip(adjacency_matrix.iter())
-                    .enumerate()
-                    .flat_map(|(row_num, (row, adjacency_row))| {
-                        row.iter_mut().zip(adjacency_row).enumerate().map(
-                            move |(column, (tile, adjacency))| (row_num, column, tile, *adjacency),
-                        )
-                    })
-            {
-                if *tile == PAPER_ROLL && adjacency < 4 {
+            for position in grid.positions() {
+                if grid[position] == PAPER_ROLL && adjacency_matrix[position] < config.threshold {
                     updated = true;
                     removed += 1;
-                    *tile = EMPTY_TILE;
-
-                    let left_column = column.checked_sub(1);
-                    let right_column = Some(column + 1).filter(|column| *column < columns);
-                    let top_row = row.checked_sub(1);
-                    let bottom_row = Some(row + 1).filter(|row| *row < rows);
-
-                    for (row, column) in [
-                        (top_row, left_column),
-                        (top_row, Some(column)),
-                        (top_row, right_column),
-                        (Some(row), left_column),
-                        (Some(row), right_column),
-                        (bottom_row, left_column),
-                        (bottom_row, Some(column)),
-                        (bottom_row, right_column),
-                    ] {
-                        if let (Some(row), Some(column)) = (row, column) {
-                            adjacency_matrix_b[row][column] -= 1;
-                        }
+                    grid[position] = EMPTY_TILE;
+
+                    for neighbor in Self::neighbors(&grid, position, &offsets, config.wrap_around) {
+                        adjacency_matrix_b[neighbor] -= 1;
                     }
                 }
             }
         }
 
+        Ok(removed)
+    }
+
+    /// Removes paper rolls from `grid` until none can be removed, calling `on_wave`
+    /// with the paper rolls removed on each wave
+    fn remove_paper_rolls(
+        grid: &mut Grid<u8>,
+        adjacency_matrix: &mut Grid<u16>,
+        offsets: &[(isize, isize)],
+        config: &FloorPlanConfig,
+        mut on_wave: impl FnMut(&[(usize, usize)]),
+    ) -> u32 {
+        // Rolls are cleared from the grid as soon as they are queued,
+        // so that they are never queued twice
+        let mut wave = vec![];
+        for position in grid.positions() {
+            if grid[position] == PAPER_ROLL && adjacency_matrix[position] < config.threshold {
+                grid[position] = EMPTY_TILE;
+                wave.push(position);
+            }
+        }
+        let mut wave_b = vec![];
+
+        let mut removed = 0;
+
+        while !wave.is_empty() {
+            on_wave(&wave);
+
+            for position in wave.drain(..) {
+                removed += 1;
+
+                for neighbor in Self::neighbors(grid, position, offsets, config.wrap_around) {
+                    adjacency_matrix[neighbor] -= 1;
+                    if grid[neighbor] == PAPER_ROLL && adjacency_matrix[neighbor] < config.threshold
+                    {
+                        grid[neighbor] = EMPTY_TILE;
+                        wave_b.push(neighbor);
+                    }
+                }
+            }
+            std::mem::swap(&mut wave, &mut wave_b);
+        }
+
         removed
     }
 
-    #[inline(always)]
-    fn gridfy_floor_plan(floor_plan: &[u8]) -> Vec<Vec<u8>> {
-        floor_plan
-            .split(|c| *c == b'\n')
-            .filter_map(|row| {
-                if !row.is_empty() {
-                    Some(row.to_vec())
-                } else {
-                    None
+    /// Parses a floor plan, where shorter rows are padded with empty tiles
+    /// and spaces are read as empty tiles
+    fn parse(floor_plan: &[u8]) -> Result<Grid<u8>, FloorPlanError> {
+        let mut grid = Grid::parse_padded(floor_plan, EMPTY_TILE);
+        if grid.is_empty() {
+            return Err(FloorPlanError::Empty);
+        }
+
+        for (row, column) in grid.positions() {
+            match grid[(row, column)] {
+                EMPTY_TILE | PAPER_ROLL | OBSTACLE => (),
+                b' ' => grid[(row, column)] = EMPTY_TILE,
+                tile => {
+                    return Err(FloorPlanError::InvalidTile {
+                        row,
+                        column,
+                        tile: char::from(tile),
+                    });
                 }
-            })
-            .collect()
+            }
+        }
+
+        Ok(grid)
     }
 
-    fn build_adjacency_matrix(grid: &[Vec<u8>]) -> Vec<Vec<u8>> {
-        let rows = grid.len();
-        let columns = grid[0].len();
-        let mut adjacency_matrix = vec![vec![0; columns]; rows];
-
-        for (row_num, row) in grid.iter().enumerate() {
-            for (column_num, _) in row.iter().enumerate().filter(|(_, c)| **c == PAPER_ROLL) {
-                let left_column = column_num.checked_sub(1);
-                let right_column = Some(column_num + 1).filter(|column| *column < columns);
-                let top_row = row_num.checked_sub(1);
-                let bottom_row = Some(row_num + 1).filter(|row| *row < rows);
-
-                for (row, column) in [
-                    (top_row, left_column),
-                    (top_row, Some(column_num)),
-                    (top_row, right_column),
-                    (Some(row_num), left_column),
-                    (Some(row_num), right_column),
-                    (bottom_row, left_column),
-                    (bottom_row, Some(column_num)),
-                    (bottom_row, right_column),
-                ] {
-                    if let (Some(row), Some(column)) = (row, column) {
-                        adjacency_matrix[row][column] += 1;
-                    }
+    /// Offsets of the neighborhood of `config`, checked against the size of the floor
+    fn offsets(
+        grid: &Grid<u8>,
+        config: &FloorPlanConfig,
+    ) -> Result<Vec<(isize, isize)>, FloorPlanError> {
+        let (Neighborhood::Moore { radius } | Neighborhood::VonNeumann { radius }) =
+            config.neighborhood;
+        if config
+            .neighborhood
+            .size()
+            .is_none_or(|size| size > usize::from(u16::MAX))
+        {
+            return Err(FloorPlanError::NeighborhoodTooLarge);
+        }
+        let diameter = 2 * radius + 1;
+        if config.wrap_around && (diameter > grid.width() || diameter > grid.height()) {
+            return Err(FloorPlanError::WrappingNeighborhoodTooWide {
+                diameter,
+                width: grid.width(),
+                height: grid.height(),
+            });
+        }
+
+        Ok(config.neighborhood.offsets())
+    }
+
+    /// Applies `update` to the counters of the neighbors of `position`,
+    /// keeping track of the paper rolls that become accessible or inaccessible
+    fn update_neighbors(&mut self, position: (usize, usize), update: fn(u16) -> u16) {
+        let Self {
+            grid,
+            adjacency_matrix,
+            offsets,
+            config,
+            accessible,
+        } = self;
+
+        for neighbor in Self::neighbors(grid, position, offsets, config.wrap_around) {
+            let was_accessible = adjacency_matrix[neighbor] < config.threshold;
+            adjacency_matrix[neighbor] = update(adjacency_matrix[neighbor]);
+            let is_accessible = adjacency_matrix[neighbor] < config.threshold;
+
+            if grid[neighbor] == PAPER_ROLL {
+                match (was_accessible, is_accessible) {
+                    (true, false) => *accessible -= 1,
+                    (false, true) => *accessible += 1,
+                    _ => (),
                 }
             }
         }
+    }
+
+    fn build_adjacency_matrix(
+        grid: &Grid<u8>,
+        offsets: &[(isize, isize)],
+        wrap_around: bool,
+    ) -> Grid<u16> {
+        let mut adjacency_matrix = Grid::new(grid.width(), grid.height(), 0);
+
+        for (position, _) in grid.enumerate().filter(|(_, c)| **c == PAPER_ROLL) {
+            for neighbor in Self::neighbors(grid, position, offsets, wrap_around) {
+                adjacency_matrix[neighbor] += 1;
+            }
+        }
 
         adjacency_matrix
     }
+
+    fn neighbors<'a>(
+        grid: &Grid<u8>,
+        position: (usize, usize),
+        offsets: &'a [(isize, isize)],
+        wrap_around: bool,
+    ) -> impl Iterator<Item = (usize, usize)> + use<'a> {
+        let (bounded, wrapping) = if wrap_around {
+            (None, Some(grid.neighbors_wrapping_at(position, offsets)))
+        } else {
+            (Some(grid.neighbors_at(position, offsets)), None)
+        };
+        bounded
+            .into_iter()
+            .flatten()
+            .chain(wrapping.into_iter().flatten())
+    }
+}
+
+impl Display for FloorPlan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.grid)
+    }
+}
+
+impl Display for FloorPlanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "Floor plan is empty."),
+            Self::InvalidTile { row, column, tile } => {
+                write!(f, "Invalid tile '{tile}' at row {row}, column {column}.")
+            }
+            Self::NeighborhoodTooLarge => {
+                write!(f, "Neighborhood has more than {} tiles.", u16::MAX)
+            }
+            Self::WrappingNeighborhoodTooWide {
+                diameter,
+                width,
+                height,
+            } => write!(
+                f,
+                "Neighborhood spanning {diameter} tiles can't wrap around a {width}x{height} floor."
+            ),
+            Self::OutOfBounds { row, column } => {
+                write!(f, "Row {row}, column {column} is outside of the floor.")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FloorPlanError {}
+
+impl RemovalReport {
+    /// Total number of paper rolls removed
+    pub fn removed(&self) -> usize {
+        self.waves.iter().map(Vec::len).sum()
+    }
+
+    /// Number of waves of removals until no more paper rolls could be removed
+    pub fn wave_count(&self) -> usize {
+        self.waves.len()
+    }
+
+    /// Positions, as `(row, column)`, of the paper rolls removed on each wave, sorted
+    pub fn waves(&self) -> &[Vec<(usize, usize)>] {
+        &self.waves
+    }
+
+    /// Floor plan after all removals, in the same format as the input
+    pub fn final_floor_plan(&self) -> String {
+        self.final_floor_plan.to_string()
+    }
+}
+
+impl Default for FloorPlanConfig {
+    /// The 8 surrounding tiles, with less than 4 paper rolls, without wrapping around
+    fn default() -> Self {
+        Self {
+            neighborhood: Neighborhood::Moore { radius: 1 },
+            threshold: 4,
+            wrap_around: false,
+            strategy: Strategy::AdjacencyMatrix,
+        }
+    }
+}
+
+impl Neighborhood {
+    /// Number of tiles in the neighborhood, excluding the center, `None` if it overflows
+    fn size(&self) -> Option<usize> {
+        match *self {
+            Self::Moore { radius } => radius
+                .checked_mul(2)?
+                .checked_add(1)?
+                .checked_pow(2)
+                .map(|tiles| tiles - 1),
+            Self::VonNeumann { radius } => {
+                radius.checked_add(1)?.checked_mul(radius)?.checked_mul(2)
+            }
+        }
+    }
+
+    /// Offsets, as `(row, column)`, of the tiles in the neighborhood, excluding the center
+    ///
+    /// The radius must have been checked with [`Neighborhood::size`].
+    fn offsets(&self) -> Vec<(isize, isize)> {
+        let (radius, max_distance) = match *self {
+            Self::Moore { radius } => (radius as isize, usize::MAX),
+            Self::VonNeumann { radius } => (radius as isize, radius),
+        };
+
+        (-radius..=radius)
+            .flat_map(|row| (-radius..=radius).map(move |column| (row, column)))
+            .filter(|(row, column)| {
+                (*row, *column) != (0, 0)
+                    && row.unsigned_abs() + column.unsigned_abs() <= max_distance
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::fixtures::{self, Xorshift64};
 
     const INPUT: &[u8] = b"..@@.@@@@.
 @@@.@.@.@@
@@ -147,11 +565,414 @@ mod tests {
 
     #[test]
     fn test_accessible_paper_rolls() {
-        assert_eq!(FloorPlan::accessible_paper_rolls(INPUT), 13);
+        assert_eq!(
+            FloorPlan::accessible_paper_rolls(INPUT, &FloorPlanConfig::default()),
+            Ok(13)
+        );
     }
 
     #[test]
     fn test_removable_paper_rolls() {
-        assert_eq!(FloorPlan::removable_paper_rolls(INPUT), 43);
+        assert_eq!(
+            FloorPlan::removable_paper_rolls(INPUT, &FloorPlanConfig::default()),
+            Ok(43)
+        );
+    }
+
+    #[test]
+    fn test_removable_paper_rolls_rescanning() {
+        assert_eq!(
+            FloorPlan::removable_paper_rolls_rescanning(INPUT, &FloorPlanConfig::default()),
+            Ok(43)
+        );
+
+        for seed in 1..=16 {
+            let floor_plan = fixtures::floor_plan(64, 48, 60 + seed, seed);
+            for config in [
+                FloorPlanConfig::default(),
+                FloorPlanConfig {
+                    neighborhood: Neighborhood::VonNeumann { radius: 2 },
+                    threshold: 6,
+                    wrap_around: true,
+                    strategy: Strategy::AdjacencyMatrix,
+                },
+            ] {
+                assert_eq!(
+                    FloorPlan::removable_paper_rolls(&floor_plan, &config),
+                    FloorPlan::removable_paper_rolls_rescanning(&floor_plan, &config),
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_bitboard() {
+        let config = FloorPlanConfig {
+            strategy: Strategy::Bitboard,
+            ..FloorPlanConfig::default()
+        };
+        assert_eq!(FloorPlan::accessible_paper_rolls(INPUT, &config), Ok(13));
+        assert_eq!(FloorPlan::removable_paper_rolls(INPUT, &config), Ok(43));
+
+        let neighborhoods = [
+            Neighborhood::Moore { radius: 1 },
+            Neighborhood::Moore { radius: 2 },
+            Neighborhood::VonNeumann { radius: 1 },
+            Neighborhood::VonNeumann { radius: 3 },
+        ];
+        for seed in 1..=4 {
+            // Widths around multiples of 64 to cross word boundaries
+            for (width, height) in [(1, 5), (63, 20), (64, 17), (65, 3), (130, 40)] {
+                let floor_plan = fixtures::floor_plan(width, height, 50 + seed * 5, seed);
+                for neighborhood in neighborhoods {
+                    for threshold in [2, 4, 7] {
+                        for wrap_around in [false, true] {
+                            let config = FloorPlanConfig {
+                                neighborhood,
+                                threshold,
+                                wrap_around,
+                                strategy: Strategy::AdjacencyMatrix,
+                            };
+                            let bitboard = FloorPlanConfig {
+                                strategy: Strategy::Bitboard,
+                                ..config
+                            };
+                            assert_eq!(
+                                FloorPlan::accessible_paper_rolls(&floor_plan, &bitboard),
+                                FloorPlan::accessible_paper_rolls(&floor_plan, &config),
+                                "{config:?} {width}x{height} seed {seed}"
+                            );
+                            assert_eq!(
+                                FloorPlan::removable_paper_rolls(&floor_plan, &bitboard),
+                                FloorPlan::removable_paper_rolls(&floor_plan, &config),
+                                "{config:?} {width}x{height} seed {seed}"
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_floor_plan_edits() {
+        let config = FloorPlanConfig::default();
+        let mut floor_plan = FloorPlan::new(INPUT, config).unwrap();
+        assert_eq!(floor_plan.accessible(), 13);
+        assert_eq!(floor_plan.removable_by_cascade(), 43);
+        assert_eq!(floor_plan.to_string().as_bytes(), INPUT);
+
+        assert!(floor_plan.is_accessible((0, 2)));
+        assert!(!floor_plan.is_accessible((0, 0)));
+        assert!(!floor_plan.is_accessible((1, 1)));
+
+        assert_eq!(floor_plan.place_paper_roll((0, 0)), Ok(true));
+        assert_eq!(floor_plan.place_paper_roll((0, 0)), Ok(false));
+        assert!(floor_plan.is_accessible((0, 0)));
+        assert_eq!(floor_plan.accessible(), 13);
+
+        assert_eq!(floor_plan.remove_paper_roll((1, 1)), Ok(true));
+        assert_eq!(floor_plan.remove_paper_roll((1, 1)), Ok(false));
+        assert!(!floor_plan.is_paper_roll((1, 1)));
+
+        let before = floor_plan.to_string();
+        assert_eq!(
+            floor_plan.place_paper_roll((10, 0)),
+            Err(FloorPlanError::OutOfBounds { row: 10, column: 0 })
+        );
+        assert_eq!(
+            floor_plan.remove_paper_roll((0, 10)),
+            Err(FloorPlanError::OutOfBounds { row: 0, column: 10 })
+        );
+        assert_eq!(floor_plan.to_string(), before);
+
+        let rendered = floor_plan.to_string();
+        assert_eq!(
+            Ok(floor_plan.accessible()),
+            FloorPlan::accessible_paper_rolls(rendered.as_bytes(), &config)
+        );
+        assert_eq!(
+            Ok(floor_plan.removable_by_cascade()),
+            FloorPlan::removable_paper_rolls(rendered.as_bytes(), &config)
+        );
+        // Queries don't modify the floor
+        assert_eq!(floor_plan.to_string(), rendered);
+    }
+
+    #[test]
+    fn test_floor_plan_random_edits() {
+        for wrap_around in [false, true] {
+            let config = FloorPlanConfig {
+                wrap_around,
+                ..FloorPlanConfig::default()
+            };
+            let mut floor_plan =
+                FloorPlan::new(&fixtures::floor_plan(30, 20, 60, 7), config).unwrap();
+
+            let mut random = Xorshift64::new(0x1234_5678);
+            for _ in 0..200 {
+                let state = random.next_u64();
+                let position = ((state % 20) as usize, ((state >> 8) % 30) as usize);
+                if state & (1 << 40) == 0 {
+                    floor_plan.place_paper_roll(position).unwrap();
+                } else {
+                    floor_plan.remove_paper_roll(position).unwrap();
+                }
+
+                let rendered = floor_plan.to_string();
+                assert_eq!(
+                    Ok(floor_plan.accessible()),
+                    FloorPlan::accessible_paper_rolls(rendered.as_bytes(), &config)
+                );
+            }
+
+            let rendered = floor_plan.to_string();
+            assert_eq!(
+                Ok(floor_plan.removable_by_cascade()),
+                FloorPlan::removable_paper_rolls(rendered.as_bytes(), &config)
+            );
+        }
+    }
+
+    #[test]
+    fn test_ragged_floor_plan() {
+        let ragged = "..@@.@@@@
+@@@.@.@.@@
+@@@@@.@.@@
+@.@@@@..@
+@@.@@@@.@@
+.@@@@@@@.@
+.@.@.@.@@@
+@.@@@.@@@@
+.@@@@@@@@
+@.@.@@@.@ ";
+        let config = FloorPlanConfig::default();
+        assert_eq!(
+            FloorPlan::accessible_paper_rolls(ragged.as_bytes(), &config),
+            Ok(13)
+        );
+        assert_eq!(
+            FloorPlan::removable_paper_rolls(ragged.as_bytes(), &config),
+            Ok(43)
+        );
+        assert_eq!(
+            FloorPlan::new(ragged.as_bytes(), config)
+                .unwrap()
+                .to_string()
+                .as_bytes(),
+            INPUT
+        );
+    }
+
+    #[test]
+    fn test_invalid_floor_plan() {
+        let config = FloorPlanConfig::default();
+        assert_eq!(
+            FloorPlan::accessible_paper_rolls(b"", &config),
+            Err(FloorPlanError::Empty)
+        );
+        assert_eq!(
+            FloorPlan::removable_paper_rolls(b"\n\n", &config),
+            Err(FloorPlanError::Empty)
+        );
+        assert_eq!(
+            FloorPlan::removable_paper_rolls(b"..@\n.x@\n", &config),
+            Err(FloorPlanError::InvalidTile {
+                row: 1,
+                column: 1,
+                tile: 'x'
+            })
+        );
+    }
+
+    #[test]
+    fn test_obstacles() {
+        let with_obstacles = b"..##.@@@@.
+@@@.@.@.@@
+@@@@@.@.@@
+@.@@@@..@.
+@@.@###.@@
+.@@@@@@@.@
+.@.@.@.@@@
+@.@@@.@@@@
+.@@@@@@@@.
+@.@.@@@.@#
+";
+        let without_obstacles = b".....@@@@.
+@@@.@.@.@@
+@@@@@.@.@@
+@.@@@@..@.
+@@.@....@@
+.@@@@@@@.@
+.@.@.@.@@@
+@.@@@.@@@@
+.@@@@@@@@.
+@.@.@@@.@.
+";
+        for strategy in [Strategy::AdjacencyMatrix, Strategy::Bitboard] {
+            let config = FloorPlanConfig {
+                strategy,
+                ..FloorPlanConfig::default()
+            };
+            assert_eq!(
+                FloorPlan::accessible_paper_rolls(with_obstacles, &config),
+                FloorPlan::accessible_paper_rolls(without_obstacles, &config)
+            );
+            assert_eq!(
+                FloorPlan::removable_paper_rolls(with_obstacles, &config),
+                FloorPlan::removable_paper_rolls(without_obstacles, &config)
+            );
+        }
+
+        let report =
+            FloorPlan::removal_report(with_obstacles, &FloorPlanConfig::default()).unwrap();
+        assert_eq!(report.removed(), 66);
+        assert_eq!(
+            report.final_floor_plan(),
+            "..##......
+..........
+..........
+..........
+....###...
+..........
+..........
+..........
+..........
+.........#
+"
+        );
+
+        let mut floor_plan = FloorPlan::new(with_obstacles, FloorPlanConfig::default()).unwrap();
+        assert_eq!(floor_plan.place_paper_roll((0, 2)), Ok(false));
+        assert_eq!(floor_plan.remove_paper_roll((0, 2)), Ok(false));
+        assert!(!floor_plan.is_paper_roll((0, 2)));
+    }
+
+    #[test]
+    fn test_removal_report() {
+        let report = FloorPlan::removal_report(INPUT, &FloorPlanConfig::default()).unwrap();
+
+        assert_eq!(report.removed(), 43);
+        assert_eq!(report.wave_count(), 9);
+        assert_eq!(
+            report.waves().iter().map(Vec::len).collect::<Vec<_>>(),
+            [13, 12, 7, 5, 2, 1, 1, 1, 1]
+        );
+        assert_eq!(report.waves()[0][..4], [(0, 2), (0, 3), (0, 5), (0, 6)]);
+        assert_eq!(report.waves()[8], [(3, 3)]);
+        assert_eq!(
+            report.final_floor_plan(),
+            "..........
+..........
+..........
+....@@....
+...@@@@...
+...@@@@@..
+...@.@.@@.
+...@@.@@@.
+...@@@@@..
+....@@@...
+"
+        );
+    }
+
+    #[test]
+    fn test_von_neumann_neighborhood() {
+        let config = FloorPlanConfig {
+            neighborhood: Neighborhood::VonNeumann { radius: 1 },
+            threshold: 2,
+            wrap_around: false,
+            strategy: Strategy::AdjacencyMatrix,
+        };
+        assert_eq!(FloorPlan::accessible_paper_rolls(INPUT, &config), Ok(11));
+        assert_eq!(FloorPlan::removable_paper_rolls(INPUT, &config), Ok(16));
+
+        let config = FloorPlanConfig {
+            threshold: 3,
+            ..config
+        };
+        assert_eq!(FloorPlan::accessible_paper_rolls(INPUT, &config), Ok(37));
+        assert_eq!(FloorPlan::removable_paper_rolls(INPUT, &config), Ok(71));
+
+        let config = FloorPlanConfig {
+            neighborhood: Neighborhood::VonNeumann { radius: 2 },
+            threshold: 6,
+            wrap_around: false,
+            strategy: Strategy::AdjacencyMatrix,
+        };
+        assert_eq!(FloorPlan::accessible_paper_rolls(INPUT, &config), Ok(17));
+        assert_eq!(FloorPlan::removable_paper_rolls(INPUT, &config), Ok(71));
+    }
+
+    #[test]
+    fn test_radius_neighborhood() {
+        let config = FloorPlanConfig {
+            neighborhood: Neighborhood::Moore { radius: 2 },
+            threshold: 12,
+            wrap_around: false,
+            strategy: Strategy::AdjacencyMatrix,
+        };
+        assert_eq!(FloorPlan::accessible_paper_rolls(INPUT, &config), Ok(25));
+        assert_eq!(FloorPlan::removable_paper_rolls(INPUT, &config), Ok(71));
+    }
+
+    #[test]
+    fn test_wrap_around() {
+        let config = FloorPlanConfig {
+            wrap_around: true,
+            ..FloorPlanConfig::default()
+        };
+        assert_eq!(FloorPlan::accessible_paper_rolls(INPUT, &config), Ok(2));
+        assert_eq!(FloorPlan::removable_paper_rolls(INPUT, &config), Ok(3));
+
+        let config = FloorPlanConfig {
+            threshold: 5,
+            ..config
+        };
+        assert_eq!(FloorPlan::accessible_paper_rolls(INPUT, &config), Ok(13));
+        assert_eq!(FloorPlan::removable_paper_rolls(INPUT, &config), Ok(71));
+    }
+
+    #[test]
+    fn test_invalid_neighborhood() {
+        let config = FloorPlanConfig {
+            neighborhood: Neighborhood::Moore { radius: 5 },
+            wrap_around: true,
+            ..FloorPlanConfig::default()
+        };
+        assert_eq!(
+            FloorPlan::accessible_paper_rolls(INPUT, &config),
+            Err(FloorPlanError::WrappingNeighborhoodTooWide {
+                diameter: 11,
+                width: 10,
+                height: 10
+            })
+        );
+        let config = FloorPlanConfig {
+            neighborhood: Neighborhood::VonNeumann { radius: 4 },
+            ..config
+        };
+        assert!(FloorPlan::new(INPUT, config).is_ok());
+        assert!(FloorPlan::new(b"@@@\n@@@\n", config).is_err());
+
+        for neighborhood in [
+            Neighborhood::Moore { radius: 128 },
+            Neighborhood::VonNeumann { radius: 181 },
+            Neighborhood::Moore { radius: usize::MAX },
+        ] {
+            let config = FloorPlanConfig {
+                neighborhood,
+                ..FloorPlanConfig::default()
+            };
+            assert_eq!(
+                FloorPlan::removable_paper_rolls(INPUT, &config),
+                Err(FloorPlanError::NeighborhoodTooLarge)
+            );
+        }
+        let config = FloorPlanConfig {
+            neighborhood: Neighborhood::Moore { radius: 127 },
+            ..FloorPlanConfig::default()
+        };
+        assert_eq!(FloorPlan::accessible_paper_rolls(INPUT, &config), Ok(0));
     }
 }
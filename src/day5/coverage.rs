@@ -0,0 +1,75 @@
+use std::ops::RangeInclusive;
+
+/// Number of ranges covering each ID, built with a sweep over the ranges' ends
+///
+/// Stored as segments where the depth is constant, each segment going from its start
+/// up to the start of the next one, with the last one going up to `u64::MAX`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coverage {
+    segments: Vec<(u64, usize)>,
+}
+
+impl Coverage {
+    pub fn new(ranges: &[RangeInclusive<u64>]) -> Self {
+        let mut events = ranges
+            .iter()
+            .filter(|range| !range.is_empty())
+            .flat_map(|range| {
+                // Ranges that go up to `u64::MAX` never end
+                [
+                    Some((*range.start(), 1)),
+                    range.end().checked_add(1).map(|end| (end, -1)),
+                ]
+            })
+            .flatten()
+            .collect::<Vec<(u64, isize)>>();
+        // Ends before starts, so that the depth never goes below 0
+        events.sort_unstable();
+
+        let mut segments = vec![(0, 0)];
+        let mut depth = 0usize;
+        for (position, change) in events {
+            depth = depth
+                .checked_add_signed(change)
+                .expect("Ranges must start before they end.");
+            match segments.last_mut() {
+                Some((start, last_depth)) if *start == position => *last_depth = depth,
+                _ => segments.push((position, depth)),
+            }
+        }
+        segments.dedup_by_key(|(_, depth)| *depth);
+
+        Self { segments }
+    }
+
+    /// Number of ranges that cover `id`
+    pub fn depth(&self, id: u64) -> usize {
+        let index = self.segments.partition_point(|(start, _)| *start <= id);
+        self.segments[index - 1].1
+    }
+
+    /// Largest number of ranges covering the same ID
+    pub fn max_depth(&self) -> usize {
+        self.segments
+            .iter()
+            .map(|(_, depth)| *depth)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Number of IDs covered by at least `k` ranges
+    pub fn covered_at_least(&self, k: usize) -> u128 {
+        self.segments
+            .iter()
+            .zip(
+                self.segments
+                    .iter()
+                    .skip(1)
+                    .map(|(start, _)| u128::from(*start))
+                    .chain([u128::from(u64::MAX) + 1]),
+            )
+            .filter(|((_, depth), _)| *depth >= k)
+            .map(|((start, _), end)| end - u128::from(*start))
+            .sum()
+    }
+}
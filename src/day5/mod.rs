@@ -1,39 +1,87 @@
-use std::ops::RangeInclusive;
+mod coverage;
+
+use std::{io::BufRead, ops::RangeInclusive};
+
+use crate::range_set::RangeSet;
+
+pub use coverage::Coverage;
 
 pub struct Inventory;
 
+/// Freshness of the listed items and of the whole ID space
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InventoryReport {
+    fresh_listed_items: Vec<u64>,
+    spoiled_listed_items: Vec<u64>,
+    ranges: RangeSet,
+}
+
 impl Inventory {
-    pub fn spoiled_items(ranges_and_items: &str) -> usize {
-        let (mut ranges, items) = Self::extract_ranges_and_items(ranges_and_items);
-        ranges.sort_by_cached_key(|range| *range.end());
+    pub fn report(ranges_and_items: &str) -> InventoryReport {
+        let (ranges, items) = Self::extract_ranges_and_items(ranges_and_items);
+        let ranges = RangeSet::from_iter(ranges);
+        let (fresh_listed_items, spoiled_listed_items) =
+            items.into_iter().partition(|item| ranges.contains(*item));
+
+        InventoryReport {
+            fresh_listed_items,
+            spoiled_listed_items,
+            ranges,
+        }
+    }
+
+    /// Number of ranges covering each of the items, in the order they are listed
+    pub fn item_coverage(ranges_and_items: &str) -> Vec<(u64, usize)> {
+        let (ranges, items) = Self::extract_ranges_and_items(ranges_and_items);
+        let coverage = Coverage::new(&ranges);
         items
             .into_iter()
-            .filter(|item| Self::is_fresh(*item, &ranges))
-            .count()
-    }
-
-    pub fn fresh_items(ranges_and_items: &str) -> usize {
-        let mut ranges = Self::extract_ranges_only(ranges_and_items);
-        ranges.sort_by_cached_key(|range| *range.start());
-
-        let mut iter = ranges.into_iter();
-        let Some(mut head) = iter.next() else {
-            unreachable!("Ranges will never be empty.");
-        };
-        let mut count = 0;
-
-        for range in iter {
-            if head.end() < range.start() {
-                count += head.count();
-                head = range
-            } else {
-                count += (*head.start()..*range.start()).count();
-                head = *range.start()..=*(head.end().max(range.end()));
-            }
-        }
-        count += head.count();
+            .map(|item| (item, coverage.depth(item)))
+            .collect()
+    }
+
+    pub fn coverage(ranges_and_items: &str) -> Coverage {
+        Coverage::new(&Self::extract_ranges_only(ranges_and_items))
+    }
+
+    /// Builds an index of the fresh ranges, ignoring the listed items,
+    /// to check the freshness of items that come later
+    pub fn range_index(ranges_and_items: &str) -> RangeSet {
+        RangeSet::from_iter(Self::extract_ranges_only(ranges_and_items))
+    }
 
-        count
+    /// Checks the freshness of items read from `items`, one per line, against
+    /// an index built by [`Inventory::range_index`] or read with [`RangeSet::read_from`]
+    ///
+    /// Empty lines are skipped, lines that are not an `u64` are reported as
+    /// [`std::io::ErrorKind::InvalidData`].
+    pub fn check_items<R: BufRead>(
+        index: &RangeSet,
+        mut items: R,
+    ) -> impl Iterator<Item = std::io::Result<(u64, bool)>> {
+        let mut line = String::new();
+        std::iter::from_fn(move || {
+            loop {
+                line.clear();
+                match items.read_line(&mut line) {
+                    Ok(0) => return None,
+                    Ok(_) => (),
+                    Err(err) => return Some(Err(err)),
+                }
+
+                let item = line.trim_end_matches(['\n', '\r']);
+                if item.is_empty() {
+                    continue;
+                }
+                return Some(match item.parse::<u64>() {
+                    Ok(item) => Ok((item, index.contains(item))),
+                    Err(err) => Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("Item '{item}' is not an u64: {err}"),
+                    )),
+                });
+            }
+        })
     }
 
     fn extract_ranges_and_items(ranges_and_items: &str) -> (Vec<RangeInclusive<u64>>, Vec<u64>) {
@@ -92,9 +140,27 @@ impl Inventory {
             })
             .collect()
     }
+}
+
+impl InventoryReport {
+    /// Listed items covered by at least one range, in the order they are listed
+    pub fn fresh_listed_items(&self) -> &[u64] {
+        &self.fresh_listed_items
+    }
+
+    /// Listed items not covered by any range, in the order they are listed
+    pub fn spoiled_listed_items(&self) -> &[u64] {
+        &self.spoiled_listed_items
+    }
+
+    /// Number of IDs covered by at least one range, whether listed or not
+    pub fn fresh_id_count(&self) -> u128 {
+        self.ranges.len()
+    }
 
-    fn is_fresh(item: u64, ranges: &[RangeInclusive<u64>]) -> bool {
-        ranges.iter().any(|range| range.contains(&item))
+    /// Ranges of fresh IDs, merged
+    pub fn merged_ranges(&self) -> &RangeSet {
+        &self.ranges
     }
 }
 
@@ -116,12 +182,116 @@ mod tests {
 ";
 
     #[test]
-    fn test_spoiled() {
-        assert_eq!(Inventory::spoiled_items(INPUT), 3);
+    fn test_report() {
+        let report = Inventory::report(INPUT);
+        assert_eq!(report.fresh_listed_items(), [5, 11, 17]);
+        assert_eq!(report.spoiled_listed_items(), [1, 8, 32]);
+        assert_eq!(report.fresh_id_count(), 14);
+        assert_eq!(
+            report.merged_ranges().iter().collect::<Vec<_>>(),
+            [&(3..=5), &(10..=20)]
+        );
+    }
+
+    #[test]
+    fn test_fresh_id_count_full_domain() {
+        let report = Inventory::report("0-18446744073709551615\n\n0\n18446744073709551615\n");
+        assert_eq!(report.fresh_id_count(), 1 << 64);
+        assert_eq!(report.fresh_listed_items(), [0, u64::MAX]);
+
+        let report = Inventory::report(
+            "5-10\n18446744073709551615-18446744073709551615\n0-18446744073709551615\n\n",
+        );
+        assert_eq!(report.fresh_id_count(), 1 << 64);
+        assert_eq!(
+            report.merged_ranges().iter().collect::<Vec<_>>(),
+            [&(0..=u64::MAX)]
+        );
+    }
+
+    #[test]
+    fn test_fresh_id_count_touching_ranges() {
+        // Touching ranges are merged, overlapping ends are not counted twice
+        let report = Inventory::report("1-3\n4-6\n6-9\n11-11\n12-12\n\n");
+        assert_eq!(report.fresh_id_count(), 11);
+        assert_eq!(
+            report.merged_ranges().iter().collect::<Vec<_>>(),
+            [&(1..=9), &(11..=12)]
+        );
+
+        let report = Inventory::report(
+            "0-9223372036854775807\n9223372036854775808-18446744073709551614\n\n",
+        );
+        assert_eq!(report.fresh_id_count(), u128::from(u64::MAX));
+        assert_eq!(
+            report.merged_ranges().iter().collect::<Vec<_>>(),
+            [&(0..=(u64::MAX - 1))]
+        );
     }
 
     #[test]
-    fn test_fresh() {
-        assert_eq!(Inventory::fresh_items(INPUT), 14);
+    fn test_item_coverage() {
+        assert_eq!(
+            Inventory::item_coverage(INPUT),
+            [(1, 0), (5, 1), (8, 0), (11, 1), (17, 2), (32, 0)]
+        );
+    }
+
+    #[test]
+    fn test_coverage() {
+        let coverage = Inventory::coverage(INPUT);
+        assert_eq!(coverage.max_depth(), 2);
+        assert_eq!(coverage.covered_at_least(0), 1 << 64);
+        assert_eq!(coverage.covered_at_least(1), 14);
+        assert_eq!(coverage.covered_at_least(2), 6);
+        assert_eq!(coverage.covered_at_least(3), 0);
+        assert_eq!(coverage.depth(0), 0);
+        assert_eq!(coverage.depth(12), 2);
+        assert_eq!(coverage.depth(15), 1);
+        assert_eq!(coverage.depth(u64::MAX), 0);
+
+        let coverage = Coverage::new(&[0..=u64::MAX, 10..=20, 20..=u64::MAX, 20..=20]);
+        assert_eq!(coverage.max_depth(), 4);
+        assert_eq!(coverage.covered_at_least(1), 1 << 64);
+        assert_eq!(coverage.covered_at_least(2), u128::from(u64::MAX - 10) + 1);
+        assert_eq!(coverage.covered_at_least(4), 1);
+        assert_eq!(coverage.depth(u64::MAX), 2);
+        assert_eq!(coverage.depth(21), 2);
+        assert_eq!(coverage.depth(9), 1);
+    }
+
+    #[test]
+    fn test_check_items() {
+        let index = Inventory::range_index(INPUT);
+        assert_eq!(&index, Inventory::report(INPUT).merged_ranges());
+
+        let mut buffer = vec![];
+        index.write_to(&mut buffer).unwrap();
+        let index = RangeSet::read_from(buffer.as_slice()).unwrap();
+
+        let items = "1\n5\r\n\n8\n11\n17\n32";
+        let checked = Inventory::check_items(&index, items.as_bytes())
+            .collect::<std::io::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            checked,
+            [
+                (1, false),
+                (5, true),
+                (8, false),
+                (11, true),
+                (17, true),
+                (32, false)
+            ]
+        );
+
+        let mut checked = Inventory::check_items(&index, "12\nx\n3\n".as_bytes());
+        assert_eq!(checked.next().unwrap().unwrap(), (12, true));
+        assert_eq!(
+            checked.next().unwrap().unwrap_err().kind(),
+            std::io::ErrorKind::InvalidData
+        );
+        assert_eq!(checked.next().unwrap().unwrap(), (3, true));
+        assert!(checked.next().is_none());
     }
 }
@@ -0,0 +1,93 @@
+use std::fmt::Display;
+
+use super::{ArithmeticError, Operator};
+
+/// Order in which the operators of an expression are applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Evaluation {
+    /// `^` first and from right to left, then `*`, `/` and `%`,
+    /// then `+` and `-`, then `<` and `>`, each level from left to right
+    #[default]
+    Precedence,
+    /// Every operator from left to right, whatever its kind
+    LeftToRight,
+}
+
+/// Tree of binary operations over the numbers of a problem
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expression {
+    Number(u64),
+    Operation {
+        operator: Operator,
+        lhs: Box<Expression>,
+        rhs: Box<Expression>,
+    },
+}
+
+impl Expression {
+    /// Builds the tree of `numbers`, where `operators[i]` sits between
+    /// `numbers[i]` and `numbers[i + 1]`
+    pub(super) fn new(numbers: &[u64], operators: &[Operator], evaluation: Evaluation) -> Self {
+        match evaluation {
+            Evaluation::Precedence => {
+                let mut index = 0;
+                Self::climb(numbers, operators, &mut index, 0)
+            }
+            Evaluation::LeftToRight => operators.iter().zip(&numbers[1..]).fold(
+                Self::Number(numbers[0]),
+                |lhs, (operator, rhs)| Self::Operation {
+                    operator: *operator,
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(Self::Number(*rhs)),
+                },
+            ),
+        }
+    }
+
+    pub fn evaluate(&self) -> Result<u64, ArithmeticError> {
+        match self {
+            Self::Number(number) => Ok(*number),
+            Self::Operation { operator, lhs, rhs } => {
+                operator.apply(lhs.evaluate()?, rhs.evaluate()?)
+            }
+        }
+    }
+
+    /// Precedence climbing, starting on `numbers[*index]` and only consuming
+    /// operators binding at least as strongly as `min_precedence`
+    fn climb(
+        numbers: &[u64],
+        operators: &[Operator],
+        index: &mut usize,
+        min_precedence: u8,
+    ) -> Self {
+        let mut lhs = Self::Number(numbers[*index]);
+        while let Some(operator) = operators.get(*index).copied()
+            && operator.precedence() >= min_precedence
+        {
+            *index += 1;
+            let next_precedence = if operator.is_right_associative() {
+                operator.precedence()
+            } else {
+                operator.precedence() + 1
+            };
+            let rhs = Self::climb(numbers, operators, index, next_precedence);
+            lhs = Self::Operation {
+                operator,
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+            };
+        }
+        lhs
+    }
+}
+
+impl Display for Expression {
+    /// Writes the expression with every operation in parentheses
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Number(number) => write!(f, "{number}"),
+            Self::Operation { operator, lhs, rhs } => write!(f, "({lhs} {operator} {rhs})"),
+        }
+    }
+}
@@ -1,154 +1,812 @@
-use std::ops::{Add, Mul};
+mod expression;
 
-#[derive(Debug)]
+use std::{fmt::Display, ops::RangeInclusive};
+
+use crate::big_uint::BigUint;
+
+pub use expression::{Evaluation, Expression};
+
+/// Size limit, in bits, of the result of an exponent in arbitrary precision
+const MAX_EXACT_BITS: u64 = 1 << 20;
+
+/// Tab stops of cephalopodian worksheets
+const TAB_WIDTH: usize = 8;
+
+/// Columns, numbers and operator token of a problem written with one number per row
+type RowProblem<'a> = (RangeInclusive<usize>, Vec<u64>, &'a str);
+
+#[derive(Debug, PartialEq, Eq)]
 pub struct Worksheet {
     operations: Vec<Operation>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorksheetError {
+    /// Problem, counted from the leftmost one, could not be solved
+    Problem {
+        problem: usize,
+        error: ArithmeticError,
+    },
+    /// Sum of the results of all problems does not fit in an `u64`
+    TotalOverflow,
+    Parse(ParseError),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// Character that is neither a space nor a tab, nor a digit on number rows,
+    /// nor an operator on the last row, at the given line and column, tabs expanded
+    InvalidCharacter {
+        line: usize,
+        column: usize,
+        character: char,
+    },
+    /// Problem spanning `columns` has no operator
+    MissingOperator { columns: RangeInclusive<usize> },
+    /// Second operator found in the columns of a single problem
+    ExtraOperator { column: usize },
+    /// Operator token of the problem spanning `columns` is not a single operator
+    InvalidOperator { columns: RangeInclusive<usize> },
+    /// Row of numbers, counted from the top, does not have one number per operator
+    UnevenRow {
+        line: usize,
+        numbers: usize,
+        expected: usize,
+    },
+    /// Expression spanning `columns` has neither a single operator nor one per gap
+    /// between its numbers
+    OperatorCount {
+        columns: RangeInclusive<usize>,
+        operators: usize,
+        expected: usize,
+    },
+    /// Problem spanning `columns` has an operator but no rows of numbers
+    EmptyProblem { columns: RangeInclusive<usize> },
+    /// Digits of the column do not fit in an `u64`
+    NumberOverflow { column: usize },
+    /// Column of a problem, in a layout with one number per column, has no digit
+    /// on any row of numbers, like an operator misaligned with its problem
+    EmptyColumn { column: usize },
+    /// Token on the given line and columns, in a layout with one number per row,
+    /// is not an `u64`
+    InvalidNumber {
+        line: usize,
+        columns: RangeInclusive<usize>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArithmeticError {
+    /// Result does not fit in an `u64`, or in 2^20 bits in arbitrary precision,
+    /// or went below 0
+    Overflow,
+    DivisionByZero,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderError {
+    /// Problem does not have as many numbers as the first one,
+    /// which can't be written with one number per row
+    UnevenProblem {
+        problem: usize,
+        numbers: usize,
+        expected: usize,
+    },
+}
+
+/// Solved problems of a worksheet, in the order they are read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorksheetReport {
+    problems: Vec<ProblemReport>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProblemReport {
+    columns: RangeInclusive<usize>,
+    numbers: Vec<u64>,
+    operator: Operator,
+    value: Result<u64, ArithmeticError>,
+}
+
 impl Worksheet {
-    pub fn solve_worksheet(worksheet: &str) -> u64 {
-        let worksheet = Self::parse(worksheet);
-        worksheet
+    pub fn solve_worksheet(worksheet: &str) -> Result<u64, WorksheetError> {
+        Self::report(worksheet)?.total()
+    }
+
+    pub fn solve_cephalopodian_worksheet(worksheet: &str) -> Result<u64, WorksheetError> {
+        Self::cephalopodian_report(worksheet)?.total()
+    }
+
+    /// Solves the worksheet in arbitrary precision, so that only subtractions
+    /// going below 0, divisions by zero and huge exponents fail
+    pub fn solve_worksheet_exact(worksheet: &str) -> Result<BigUint, WorksheetError> {
+        Self::parse(worksheet)?.solve_exact()
+    }
+
+    pub fn solve_cephalopodian_worksheet_exact(worksheet: &str) -> Result<BigUint, WorksheetError> {
+        Self::parse_cephalopodian(worksheet)?.solve_exact()
+    }
+
+    pub fn report(worksheet: &str) -> Result<WorksheetReport, ParseError> {
+        Ok(Self::parse(worksheet)?.solve())
+    }
+
+    pub fn cephalopodian_report(worksheet: &str) -> Result<WorksheetReport, ParseError> {
+        Ok(Self::parse_cephalopodian(worksheet)?.solve())
+    }
+
+    fn solve(self) -> WorksheetReport {
+        let problems = self
             .operations
-            .iter()
-            .map(|operation| operation.solve())
-            .sum()
+            .into_iter()
+            .map(|operation| ProblemReport {
+                value: operation.solve(),
+                columns: operation.columns,
+                numbers: operation.numbers,
+                operator: operation.operator,
+            })
+            .collect();
+
+        WorksheetReport { problems }
     }
 
-    pub fn solve_cephalopodian_worksheet(worksheet: &str) -> u64 {
-        let worksheet = Self::parse_cephalopodian(worksheet);
-        worksheet
+    fn solve_exact(&self) -> Result<BigUint, WorksheetError> {
+        self.operations.iter().enumerate().try_fold(
+            BigUint::zero(),
+            |total, (problem, operation)| {
+                let value = operation
+                    .solve_exact()
+                    .map_err(|error| WorksheetError::Problem { problem, error })?;
+                Ok(total + &value)
+            },
+        )
+    }
+
+    /// Writes the worksheet with one number per row, right aligned on the columns of its problem
+    pub fn render(&self) -> Result<String, RenderError> {
+        let rows = self
+            .operations
+            .first()
+            .map_or(0, |operation| operation.numbers.len());
+        if let Some((problem, operation)) = self
             .operations
             .iter()
-            .map(|operation| operation.solve())
-            .sum()
-    }
+            .enumerate()
+            .find(|(_, operation)| operation.numbers.len() != rows)
+        {
+            return Err(RenderError::UnevenProblem {
+                problem,
+                numbers: operation.numbers.len(),
+                expected: rows,
+            });
+        }
 
-    fn parse(worksheet: &str) -> Worksheet {
-        let mut lines = worksheet.lines().rev();
+        let widths = self
+            .operations
+            .iter()
+            .map(|operation| {
+                operation
+                    .numbers
+                    .iter()
+                    .map(|number| number.to_string().len())
+                    .max()
+                    .unwrap_or(1)
+            })
+            .collect::<Vec<_>>();
 
-        let Some(operators) = lines.next() else {
-            unreachable!("Lines will never be empty.");
-        };
+        let mut lines = (0..rows)
+            .map(|row| {
+                self.operations
+                    .iter()
+                    .zip(widths.iter())
+                    .map(|(operation, width)| format!("{:>width$}", operation.numbers[row]))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect::<Vec<_>>();
+        lines.push(
+            self.operations
+                .iter()
+                .zip(widths.iter())
+                .map(|(operation, width)| format!("{:<width$}", operation.operator.to_string()))
+                .collect::<Vec<_>>()
+                .join(" "),
+        );
+
+        Ok(lines.into_iter().map(|line| line + "\n").collect())
+    }
+
+    /// Writes the worksheet with one number per column, digits read from top to bottom
+    pub fn render_cephalopodian(&self) -> String {
+        let digits = self
+            .operations
+            .iter()
+            .map(|operation| {
+                operation
+                    .numbers
+                    .iter()
+                    .map(|number| number.to_string().into_bytes())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let rows = digits.iter().flatten().map(Vec::len).max().unwrap_or(0);
 
-        let mut number_rows = lines
+        let mut lines = (0..rows)
             .map(|row| {
-                row.split(" ")
-                    .filter_map(|number| number.parse::<u64>().ok())
+                digits
+                    .iter()
+                    .map(|numbers| {
+                        numbers
+                            .iter()
+                            .map(|number| char::from(number.get(row).copied().unwrap_or(b' ')))
+                            .collect::<String>()
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ")
             })
             .collect::<Vec<_>>();
+        lines.push(
+            self.operations
+                .iter()
+                .map(|operation| {
+                    format!(
+                        "{:<width$}",
+                        operation.operator.to_string(),
+                        width = operation.numbers.len()
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(" "),
+        );
 
-        let mut operations = vec![];
+        lines.into_iter().map(|line| line + "\n").collect()
+    }
+
+    /// Reads problems written with one number per row, separated by spaces
+    pub fn parse(worksheet: &str) -> Result<Worksheet, ParseError> {
+        let operations = Self::read_rows(worksheet)?
+            .into_iter()
+            .map(|(columns, numbers, operator)| {
+                let operator = match operator.chars().collect::<Vec<_>>()[..] {
+                    [operator] => Operator::from_char(operator),
+                    _ => None,
+                };
+                let Some(operator) = operator else {
+                    return Err(ParseError::InvalidOperator { columns });
+                };
+                Ok(Operation {
+                    columns,
+                    numbers,
+                    operator,
+                })
+            })
+            .collect::<Result<_, _>>()?;
 
-        for operator in operators.split(" ").filter_map(|operator| {
-            if operator.is_empty() {
-                None
-            } else {
-                match operator.trim() {
-                    "+" => Some(Operator::Sum),
-                    "*" => Some(Operator::Multiplication),
-                    _ => unreachable!("Operator input must be well formatted."),
+        Ok(Worksheet { operations })
+    }
+
+    /// Reads problems written with one number per row, each made of a number expression
+    /// with one operator per gap between its numbers, like `+*+` for 4 numbers
+    ///
+    /// A single operator is used for every gap and folds the numbers from left to right
+    /// like in [`Worksheet::parse`], whatever the `evaluation`, so `^` is not right associative.
+    pub fn parse_expressions(
+        worksheet: &str,
+        evaluation: Evaluation,
+    ) -> Result<Vec<Expression>, ParseError> {
+        Self::read_rows(worksheet)?
+            .into_iter()
+            .map(|(columns, numbers, operators)| {
+                let Some(operators) = operators
+                    .chars()
+                    .map(Operator::from_char)
+                    .collect::<Option<Vec<_>>>()
+                else {
+                    return Err(ParseError::InvalidOperator { columns });
+                };
+                let gaps = numbers.len() - 1;
+                match operators[..] {
+                    [operator] => Ok(Expression::new(
+                        &numbers,
+                        &vec![operator; gaps],
+                        Evaluation::LeftToRight,
+                    )),
+                    _ if operators.len() == gaps => {
+                        Ok(Expression::new(&numbers, &operators, evaluation))
+                    }
+                    _ => Err(ParseError::OperatorCount {
+                        columns,
+                        operators: operators.len(),
+                        expected: gaps,
+                    }),
                 }
-            }
-        }) {
-            let Some(numbers) = number_rows
-                .iter_mut()
-                .map(|number| number.next())
-                .collect::<Option<Vec<u64>>>()
-            else {
-                unreachable!("All rows must have the same number of items as the operators row.");
-            };
-            operations.push(Operation { numbers, operator });
-        }
+            })
+            .collect()
+    }
+
+    pub fn solve_expressions(
+        worksheet: &str,
+        evaluation: Evaluation,
+    ) -> Result<u64, WorksheetError> {
+        Self::parse_expressions(worksheet, evaluation)?
+            .iter()
+            .enumerate()
+            .try_fold(0u64, |total, (problem, expression)| {
+                let value = expression
+                    .evaluate()
+                    .map_err(|error| WorksheetError::Problem { problem, error })?;
+                total
+                    .checked_add(value)
+                    .ok_or(WorksheetError::TotalOverflow)
+            })
+    }
 
-        Worksheet { operations }
+    /// Lines of the worksheet, without the trailing blank lines,
+    /// so that the last one is the operator row
+    fn lines(worksheet: &str) -> Vec<&str> {
+        let mut lines = worksheet.lines().collect::<Vec<_>>();
+        while lines.last().is_some_and(|line| line.trim().is_empty()) {
+            lines.pop();
+        }
+        lines
     }
 
-    fn parse_cephalopodian(worksheet: &str) -> Worksheet {
-        let mut lines = worksheet.lines().rev();
+    /// Splits the worksheet in problems of one number per row, with the columns
+    /// they span and their operator token
+    fn read_rows(worksheet: &str) -> Result<Vec<RowProblem<'_>>, ParseError> {
+        let lines = Self::lines(worksheet);
+        let Some((operators, number_rows)) = lines.split_last() else {
+            return Ok(vec![]);
+        };
+        let operators = tokens(operators).collect::<Vec<_>>();
+
+        let number_rows = number_rows
+            .iter()
+            .enumerate()
+            .map(|(line, row)| {
+                let numbers = tokens(row)
+                    .map(|(columns, number)| match number.parse::<u64>() {
+                        Ok(number) => Ok((columns, number)),
+                        Err(_) => Err(ParseError::InvalidNumber { line, columns }),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                if numbers.len() != operators.len() {
+                    return Err(ParseError::UnevenRow {
+                        line,
+                        numbers: numbers.len(),
+                        expected: operators.len(),
+                    });
+                }
+                Ok(numbers)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        operators
+            .into_iter()
+            .enumerate()
+            .map(|(problem, (columns, operator))| {
+                if number_rows.is_empty() {
+                    return Err(ParseError::EmptyProblem { columns });
+                }
+                let (start, end) = number_rows.iter().map(|row| &row[problem].0).fold(
+                    (*columns.start(), *columns.end()),
+                    |(start, end), columns| (start.min(*columns.start()), end.max(*columns.end())),
+                );
+                let numbers = number_rows.iter().map(|row| row[problem].1).collect();
+                Ok((start..=end, numbers, operator))
+            })
+            .collect()
+    }
 
-        let Some(operators) = lines.next() else {
-            unreachable!("Lines will never be empty.");
+    /// Reads problems written with one number per column, separated by blank columns
+    ///
+    /// Rows may be shorter than the others, as if their trailing spaces were trimmed,
+    /// and tabs are expanded to the next multiple of 8 columns.
+    pub fn parse_cephalopodian(worksheet: &str) -> Result<Worksheet, ParseError> {
+        let lines = Self::lines(worksheet);
+        let Some((operators, number_rows)) = lines.split_last() else {
+            return Ok(Worksheet { operations: vec![] });
         };
 
-        let mut number_rows = lines.map(|line| line.chars().rev()).collect::<Vec<_>>();
-        number_rows.reverse();
+        let number_rows = number_rows
+            .iter()
+            .enumerate()
+            .map(|(line, row)| expand_tabs(row, line, |character| character.is_ascii_digit()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let operators = expand_tabs(operators, number_rows.len(), |character| {
+            Operator::from_char(character).is_some()
+        })?;
+
+        let rows = || number_rows.iter().chain([&operators]);
+        let cell = |row: &[char], column: usize| row.get(column).copied().unwrap_or(' ');
+        let is_blank = |column| rows().all(|row| cell(row, column) == ' ');
+        let width = rows().map(Vec::len).max().unwrap_or(0);
 
         let mut operations = vec![];
-        let mut number_cache = vec![];
+        let mut column = 0;
+        while column < width {
+            if is_blank(column) {
+                column += 1;
+                continue;
+            }
+            let start = column;
+            while column < width && !is_blank(column) {
+                column += 1;
+            }
+            let columns = start..=(column - 1);
 
-        let mut next_is_skip = false;
-        for operator in operators.chars().rev() {
-            if next_is_skip {
-                for row in number_rows.iter_mut() {
-                    let _ = row.next();
-                }
-                next_is_skip = false;
-            } else {
-                let mut number = 0;
-                for row in number_rows.iter_mut() {
-                    let Some(digit) = row.next() else {
-                        unreachable!(
-                            "All rows must have the same number of characters as the operators."
-                        );
-                    };
-                    if digit != ' ' {
-                        number = number * 10 + u64::from(digit.to_digit(10).expect("msg"))
-                    }
+            let mut operator = None;
+            for position in columns.clone() {
+                if let Some(symbol) = Operator::from_char(cell(&operators, position))
+                    && operator.replace(symbol).is_some()
+                {
+                    return Err(ParseError::ExtraOperator { column: position });
                 }
-                number_cache.push(number);
-
-                match operator {
-                    '+' => {
-                        operations.push(Operation {
-                            numbers: std::mem::take(&mut number_cache),
-                            operator: Operator::Sum,
-                        });
-                        next_is_skip = true;
-                    }
-                    '*' => {
-                        operations.push(Operation {
-                            numbers: std::mem::take(&mut number_cache),
-                            operator: Operator::Multiplication,
-                        });
-                        next_is_skip = true;
+            }
+            let Some(operator) = operator else {
+                return Err(ParseError::MissingOperator { columns });
+            };
+
+            // Digits are read from top to bottom
+            let numbers = columns
+                .clone()
+                .map(|position| {
+                    let mut digits = number_rows
+                        .iter()
+                        .filter_map(|row| cell(row, position).to_digit(10))
+                        .peekable();
+                    if digits.peek().is_none() {
+                        return Err(ParseError::EmptyColumn { column: position });
                     }
-                    ' ' => (),
-                    _ => unreachable!("Input must be well formatted."),
-                }
+                    digits
+                        .try_fold(0u64, |number, digit| {
+                            number.checked_mul(10)?.checked_add(u64::from(digit))
+                        })
+                        .ok_or(ParseError::NumberOverflow { column: position })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            operations.push(Operation {
+                columns,
+                numbers,
+                operator,
+            });
+        }
+
+        Ok(Worksheet { operations })
+    }
+}
+
+/// Expands the tabs of `line`, checking that every other character is a space or `is_valid`
+fn expand_tabs(
+    line: &str,
+    line_index: usize,
+    is_valid: impl Fn(char) -> bool,
+) -> Result<Vec<char>, ParseError> {
+    let mut expanded = vec![];
+    for character in line.chars() {
+        match character {
+            '\t' => expanded.resize((expanded.len() / TAB_WIDTH + 1) * TAB_WIDTH, ' '),
+            ' ' => expanded.push(' '),
+            character if is_valid(character) => expanded.push(character),
+            character => {
+                return Err(ParseError::InvalidCharacter {
+                    line: line_index,
+                    column: expanded.len(),
+                    character,
+                });
             }
         }
+    }
+    Ok(expanded)
+}
+
+/// Splits `line` on spaces, along with the columns of each non empty token,
+/// counted in characters like in [`expand_tabs`]
+fn tokens(line: &str) -> impl Iterator<Item = (RangeInclusive<usize>, &str)> {
+    line.split(' ')
+        .scan(0, |start, token| {
+            let width = token.chars().count();
+            let columns = *start..=(*start + width).saturating_sub(1);
+            *start += width + 1;
+            Some((columns, token))
+        })
+        .filter(|(_, token)| !token.is_empty())
+}
 
-        Worksheet { operations }
+impl WorksheetReport {
+    /// Sum of the values of all problems, or the error of the first problem that could not be solved
+    pub fn total(&self) -> Result<u64, WorksheetError> {
+        self.problems
+            .iter()
+            .enumerate()
+            .try_fold(0u64, |total, (problem, report)| {
+                let value = report
+                    .value
+                    .map_err(|error| WorksheetError::Problem { problem, error })?;
+                total
+                    .checked_add(value)
+                    .ok_or(WorksheetError::TotalOverflow)
+            })
+    }
+
+    /// Problems from the leftmost to the rightmost
+    pub fn problems(&self) -> &[ProblemReport] {
+        &self.problems
+    }
+}
+
+impl ProblemReport {
+    /// Columns of the worksheet the problem was read from, operator included
+    pub fn columns(&self) -> RangeInclusive<usize> {
+        self.columns.clone()
+    }
+
+    /// Numbers in the order they are folded
+    pub fn numbers(&self) -> &[u64] {
+        &self.numbers
+    }
+
+    pub fn operator(&self) -> Operator {
+        self.operator
+    }
+
+    pub fn value(&self) -> Result<u64, ArithmeticError> {
+        self.value
     }
 }
 
 #[derive(Debug)]
 struct Operation {
+    columns: RangeInclusive<usize>,
     numbers: Vec<u64>,
     operator: Operator,
 }
 
+/// Columns only tell where the problem was read from, so they are left out
+impl PartialEq for Operation {
+    fn eq(&self, other: &Self) -> bool {
+        self.numbers == other.numbers && self.operator == other.operator
+    }
+}
+
+impl Eq for Operation {}
+
 impl Operation {
-    fn solve(&self) -> u64 {
-        let func: fn(u64, u64) -> u64 = match self.operator {
-            Operator::Sum => Add::add,
-            Operator::Multiplication => Mul::mul,
-        };
-        self.numbers
-            .iter()
-            .copied()
-            .reduce(func)
-            .expect("Numbers will never be empty")
+    /// Folds the numbers in reading order, from top to bottom or from left to right
+    fn solve(&self) -> Result<u64, ArithmeticError> {
+        let mut numbers = self.numbers.iter().copied();
+        let first = numbers.next().expect("Numbers will never be empty");
+        numbers.try_fold(first, |lhs, rhs| self.operator.apply(lhs, rhs))
+    }
+
+    fn solve_exact(&self) -> Result<BigUint, ArithmeticError> {
+        let mut numbers = self.numbers.iter().copied();
+        let first = numbers.next().expect("Numbers will never be empty");
+        numbers.try_fold(BigUint::from(first), |lhs, rhs| {
+            self.operator.apply_exact(lhs, rhs)
+        })
     }
 }
 
-#[derive(Debug)]
-enum Operator {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
     Sum,
     Multiplication,
+    Subtraction,
+    Division,
+    Modulo,
+    Exponent,
+    Minimum,
+    Maximum,
+}
+
+impl Operator {
+    fn from_char(operator: char) -> Option<Self> {
+        match operator {
+            '+' => Some(Self::Sum),
+            '*' => Some(Self::Multiplication),
+            '-' => Some(Self::Subtraction),
+            '/' => Some(Self::Division),
+            '%' => Some(Self::Modulo),
+            '^' => Some(Self::Exponent),
+            '<' => Some(Self::Minimum),
+            '>' => Some(Self::Maximum),
+            _ => None,
+        }
+    }
+
+    /// Binding strength of the operator in an expression, higher binds first
+    fn precedence(self) -> u8 {
+        match self {
+            Self::Exponent => 3,
+            Self::Multiplication | Self::Division | Self::Modulo => 2,
+            Self::Sum | Self::Subtraction => 1,
+            Self::Minimum | Self::Maximum => 0,
+        }
+    }
+
+    fn is_right_associative(self) -> bool {
+        self == Self::Exponent
+    }
+
+    fn apply(self, lhs: u64, rhs: u64) -> Result<u64, ArithmeticError> {
+        match self {
+            Self::Sum => lhs.checked_add(rhs).ok_or(ArithmeticError::Overflow),
+            Self::Multiplication => lhs.checked_mul(rhs).ok_or(ArithmeticError::Overflow),
+            Self::Subtraction => lhs.checked_sub(rhs).ok_or(ArithmeticError::Overflow),
+            Self::Division => lhs.checked_div(rhs).ok_or(ArithmeticError::DivisionByZero),
+            Self::Modulo => lhs.checked_rem(rhs).ok_or(ArithmeticError::DivisionByZero),
+            Self::Exponent => match (lhs, rhs) {
+                (_, 0) => Ok(1),
+                (0 | 1, _) => Ok(lhs),
+                _ => u32::try_from(rhs)
+                    .ok()
+                    .and_then(|rhs| lhs.checked_pow(rhs))
+                    .ok_or(ArithmeticError::Overflow),
+            },
+            Self::Minimum => Ok(lhs.min(rhs)),
+            Self::Maximum => Ok(lhs.max(rhs)),
+        }
+    }
+
+    fn apply_exact(self, lhs: BigUint, rhs: u64) -> Result<BigUint, ArithmeticError> {
+        match self {
+            Self::Sum => Ok(lhs + rhs),
+            Self::Multiplication => Ok(lhs * rhs),
+            Self::Subtraction => lhs.checked_sub(rhs).ok_or(ArithmeticError::Overflow),
+            Self::Division => lhs
+                .checked_div_rem(rhs)
+                .map(|(quotient, _)| quotient)
+                .ok_or(ArithmeticError::DivisionByZero),
+            Self::Modulo => lhs
+                .checked_div_rem(rhs)
+                .map(|(_, remainder)| BigUint::from(remainder))
+                .ok_or(ArithmeticError::DivisionByZero),
+            Self::Exponent => match (lhs.to_u64(), rhs) {
+                (_, 0) => Ok(BigUint::from(1u64)),
+                (Some(0 | 1), _) => Ok(lhs),
+                _ if lhs.bits().saturating_mul(rhs) > MAX_EXACT_BITS => {
+                    Err(ArithmeticError::Overflow)
+                }
+                _ => Ok(lhs.pow(rhs as u32)),
+            },
+            Self::Minimum => Ok(lhs.min(BigUint::from(rhs))),
+            Self::Maximum => Ok(lhs.max(BigUint::from(rhs))),
+        }
+    }
+}
+
+impl Display for Operator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let symbol = match self {
+            Self::Sum => '+',
+            Self::Multiplication => '*',
+            Self::Subtraction => '-',
+            Self::Division => '/',
+            Self::Modulo => '%',
+            Self::Exponent => '^',
+            Self::Minimum => '<',
+            Self::Maximum => '>',
+        };
+        write!(f, "{symbol}")
+    }
+}
+
+impl Display for WorksheetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Problem { problem, error } => write!(f, "Problem {problem}: {error}"),
+            Self::TotalOverflow => write!(f, "Total of the worksheet does not fit in an u64."),
+            Self::Parse(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl Display for ArithmeticError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Overflow => write!(f, "Result does not fit in an u64."),
+            Self::DivisionByZero => write!(f, "Division by zero."),
+        }
+    }
 }
 
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidCharacter {
+                line,
+                column,
+                character,
+            } => write!(
+                f,
+                "Invalid character '{character}' on line {line}, column {column}."
+            ),
+            Self::MissingOperator { columns } => write!(
+                f,
+                "Problem on columns {} to {} has no operator.",
+                columns.start(),
+                columns.end()
+            ),
+            Self::ExtraOperator { column } => {
+                write!(f, "Problem has a second operator on column {column}.")
+            }
+            Self::InvalidOperator { columns } => write!(
+                f,
+                "Problem on columns {} to {} does not have a single valid operator.",
+                columns.start(),
+                columns.end()
+            ),
+            Self::UnevenRow {
+                line,
+                numbers,
+                expected,
+            } => write!(
+                f,
+                "Line {line} has {numbers} numbers instead of {expected}."
+            ),
+            Self::OperatorCount {
+                columns,
+                operators,
+                expected,
+            } => write!(
+                f,
+                "Expression on columns {} to {} has {operators} operators instead of 1 or {expected}.",
+                columns.start(),
+                columns.end()
+            ),
+            Self::EmptyProblem { columns } => write!(
+                f,
+                "Problem on columns {} to {} has no numbers.",
+                columns.start(),
+                columns.end()
+            ),
+            Self::NumberOverflow { column } => {
+                write!(f, "Number on column {column} does not fit in an u64.")
+            }
+            Self::EmptyColumn { column } => {
+                write!(f, "Column {column} of the problem has no digits.")
+            }
+            Self::InvalidNumber { line, columns } => write!(
+                f,
+                "Number on line {line}, columns {} to {}, is not an u64.",
+                columns.start(),
+                columns.end()
+            ),
+        }
+    }
+}
+
+impl From<ParseError> for WorksheetError {
+    fn from(error: ParseError) -> Self {
+        Self::Parse(error)
+    }
+}
+
+impl Display for RenderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnevenProblem {
+                problem,
+                numbers,
+                expected,
+            } => write!(
+                f,
+                "Problem {problem} has {numbers} numbers instead of {expected}."
+            ),
+        }
+    }
+}
+
+impl std::error::Error for WorksheetError {}
+
+impl std::error::Error for ArithmeticError {}
+
+impl std::error::Error for ParseError {}
+
+impl std::error::Error for RenderError {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -161,11 +819,476 @@ mod tests {
 
     #[test]
     fn test_solver() {
-        assert_eq!(Worksheet::solve_worksheet(INPUT), 4277556);
+        assert_eq!(Worksheet::solve_worksheet(INPUT), Ok(4277556));
+    }
+
+    #[test]
+    fn test_trailing_blank_lines() {
+        assert_eq!(Worksheet::solve_worksheet("1 2\n3 4\n+ *\n\n"), Ok(12));
+        assert_eq!(Worksheet::solve_worksheet("1 2\n3 4\n+ *\n \n\t\n"), Ok(12));
+        let padded = format!("{INPUT}\n   \n");
+        assert_eq!(Worksheet::solve_worksheet(&padded), Ok(4277556));
+        assert_eq!(
+            Worksheet::solve_cephalopodian_worksheet(&padded),
+            Ok(3263827)
+        );
     }
 
     #[test]
     fn test_cephalopodian_solver() {
-        assert_eq!(Worksheet::solve_cephalopodian_worksheet(INPUT), 3263827);
+        assert_eq!(Worksheet::solve_cephalopodian_worksheet(INPUT), Ok(3263827));
+    }
+
+    #[test]
+    fn test_report() {
+        let report = Worksheet::report(INPUT).unwrap();
+        let problems = report
+            .problems()
+            .iter()
+            .map(|problem| {
+                (
+                    problem.columns(),
+                    problem.numbers().to_vec(),
+                    problem.operator(),
+                    problem.value(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            problems,
+            [
+                (0..=2, vec![123, 45, 6], Operator::Multiplication, Ok(33210)),
+                (4..=6, vec![328, 64, 98], Operator::Sum, Ok(490)),
+                (
+                    8..=10,
+                    vec![51, 387, 215],
+                    Operator::Multiplication,
+                    Ok(4243455)
+                ),
+                (12..=14, vec![64, 23, 314], Operator::Sum, Ok(401)),
+            ]
+        );
+        assert_eq!(report.total(), Ok(4277556));
+
+        let report = Worksheet::cephalopodian_report(INPUT).unwrap();
+        assert_eq!(report.problems().len(), 4);
+        let first = &report.problems()[0];
+        assert_eq!(first.columns(), 0..=2);
+        assert_eq!(first.numbers(), [1, 24, 356]);
+        assert_eq!(first.value(), Ok(8544));
+        let last = &report.problems()[3];
+        assert_eq!(last.columns(), 12..=14);
+        assert_eq!(last.numbers(), [623, 431, 4]);
+        assert_eq!(last.operator().to_string(), "+");
+        assert_eq!(last.value(), Ok(1058));
+    }
+
+    const EXTENDED_INPUT: &str = "100 328  51 2 2  7 
+ 45  64 387 3 10 9 
+  6   2 215 3 3  8 
+-   /   %   ^ <  > 
+";
+
+    #[test]
+    fn test_extended_operators() {
+        // 100 - 45 - 6 + 328 / 64 / 2 + 51 % 387 % 215 + 2 ^ 3 ^ 3 + 2 + 9
+        assert_eq!(Worksheet::solve_worksheet(EXTENDED_INPUT), Ok(625));
+
+        let input = "95 84 23 7 5 17
+3  2     391   
+-  /  ^  <   % 
+";
+        // 93 - 5 + 82 / 4 + 2 ^ 3 + 9 + 1 % 7
+        assert_eq!(Worksheet::solve_cephalopodian_worksheet(input), Ok(126));
+    }
+
+    #[test]
+    fn test_exact() {
+        assert_eq!(
+            Worksheet::solve_worksheet_exact(INPUT).map(|total| total.to_u64()),
+            Ok(Some(4277556))
+        );
+        assert_eq!(
+            Worksheet::solve_cephalopodian_worksheet_exact(INPUT).map(|total| total.to_u64()),
+            Ok(Some(3263827))
+        );
+
+        // The first two problems overflow an u64
+        let input = "18446744073709551615 18446744073709551615 2  10
+18446744073709551615 1                    2  40
+18446744073709551615 0                    10 3 
+*                    +                    ^  / 
+";
+        assert_eq!(
+            Worksheet::solve_worksheet(input),
+            Err(WorksheetError::Problem {
+                problem: 0,
+                error: ArithmeticError::Overflow
+            })
+        );
+        let expected = BigUint::from(u64::MAX).pow(3) + &BigUint::from(1u128 << 64) + (1 << 20);
+        assert_eq!(Worksheet::solve_worksheet_exact(input), Ok(expected));
+        // (u64::MAX ^ 2) ^ 3, folded from left to right
+        assert_eq!(
+            Worksheet::solve_worksheet_exact("18446744073709551615\n2\n3\n^\n")
+                .map(|total| total.to_string()),
+            Ok("39402006196394479199463117884618153312446490372007876911560089010528390154342399181505217109422728930545305988890625".to_string())
+        );
+
+        assert_eq!(
+            Worksheet::solve_worksheet_exact("2 1\n3 2\n- /\n"),
+            Err(WorksheetError::Problem {
+                problem: 0,
+                error: ArithmeticError::Overflow
+            })
+        );
+        assert_eq!(
+            Worksheet::solve_worksheet_exact("3 1\n2 0\n- %\n"),
+            Err(WorksheetError::Problem {
+                problem: 1,
+                error: ArithmeticError::DivisionByZero
+            })
+        );
+        assert_eq!(
+            Worksheet::solve_worksheet_exact("2\n18446744073709551615\n^\n"),
+            Err(WorksheetError::Problem {
+                problem: 0,
+                error: ArithmeticError::Overflow
+            })
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let worksheet = Worksheet::parse(INPUT).unwrap();
+        let rendered = worksheet.render().unwrap();
+        assert_eq!(
+            rendered,
+            "123 328  51  64\n 45  64 387  23\n  6  98 215 314\n*   +   *   +  \n"
+        );
+        assert_eq!(Worksheet::parse(&rendered), Ok(worksheet));
+
+        let worksheet = Worksheet::parse_cephalopodian(INPUT).unwrap();
+        let rendered = worksheet.render_cephalopodian();
+        assert_eq!(
+            rendered,
+            "123 328 351 644\n 45 64  287 23 \n  6 98   15 31 \n*   +   *   +  \n"
+        );
+        assert_eq!(Worksheet::parse_cephalopodian(&rendered), Ok(worksheet));
+    }
+
+    #[test]
+    fn test_convert_layouts() {
+        for input in [INPUT, EXTENDED_INPUT] {
+            let worksheet = Worksheet::parse(input).unwrap();
+            let converted = worksheet.render_cephalopodian();
+            assert_eq!(Worksheet::parse_cephalopodian(&converted), Ok(worksheet));
+            assert_eq!(
+                Worksheet::solve_cephalopodian_worksheet(&converted),
+                Worksheet::solve_worksheet(input)
+            );
+        }
+
+        let worksheet = Worksheet::parse_cephalopodian(INPUT).unwrap();
+        let converted = worksheet.render().unwrap();
+        assert_eq!(Worksheet::parse(&converted), Ok(worksheet));
+        assert_eq!(Worksheet::solve_worksheet(&converted), Ok(3263827));
+
+        let worksheet = Worksheet::parse_cephalopodian("12 3\n4  5\n+  *\n").unwrap();
+        assert_eq!(
+            worksheet.render(),
+            Err(RenderError::UnevenProblem {
+                problem: 1,
+                numbers: 1,
+                expected: 2
+            })
+        );
+
+        let empty = Worksheet::parse("\n").unwrap();
+        assert_eq!(empty.render(), Ok("\n".to_string()));
+        assert_eq!(
+            Worksheet::parse_cephalopodian(&empty.render_cephalopodian()),
+            Ok(empty)
+        );
+    }
+
+    #[test]
+    fn test_cephalopodian_layout_tolerance() {
+        let trimmed = INPUT
+            .lines()
+            .map(|line| line.trim_end().to_string() + "\n")
+            .collect::<String>();
+        assert_eq!(
+            Worksheet::solve_cephalopodian_worksheet(&trimmed),
+            Ok(3263827)
+        );
+
+        // Tabs and several blank columns between problems
+        let input = "123\t328  51\n 45\t64  387\n  6\t98  215\n*\t+   *\n\n";
+        let report = Worksheet::cephalopodian_report(input).unwrap();
+        assert_eq!(
+            report
+                .problems()
+                .iter()
+                .map(ProblemReport::columns)
+                .collect::<Vec<_>>(),
+            [0..=2, 8..=10, 12..=14]
+        );
+        assert_eq!(report.total(), Ok(3262769));
+    }
+
+    #[test]
+    fn test_cephalopodian_parse_errors() {
+        assert_eq!(
+            Worksheet::parse_cephalopodian("12 3\n4x 5\n+  *\n"),
+            Err(ParseError::InvalidCharacter {
+                line: 1,
+                column: 1,
+                character: 'x'
+            })
+        );
+        assert_eq!(
+            Worksheet::parse_cephalopodian("12 3\n4  5\n+  1\n"),
+            Err(ParseError::InvalidCharacter {
+                line: 2,
+                column: 3,
+                character: '1'
+            })
+        );
+        // Columns count tabs as spaces up to the next tab stop, like the problem columns
+        assert_eq!(
+            Worksheet::parse_cephalopodian("1\t2\n4\tx\n+\t*\n"),
+            Err(ParseError::InvalidCharacter {
+                line: 1,
+                column: 8,
+                character: 'x'
+            })
+        );
+        assert_eq!(
+            Worksheet::parse_cephalopodian("12 3\n4  5\n+\n"),
+            Err(ParseError::MissingOperator { columns: 3..=3 })
+        );
+        assert_eq!(
+            Worksheet::parse_cephalopodian("123\n456\n+*\n"),
+            Err(ParseError::ExtraOperator { column: 1 })
+        );
+        assert_eq!(
+            Worksheet::solve_cephalopodian_worksheet(&("9\n".repeat(20) + "+\n")),
+            Err(WorksheetError::Parse(ParseError::NumberOverflow {
+                column: 0
+            }))
+        );
+        // Operators misaligned with their problem don't make up a 0
+        assert_eq!(
+            Worksheet::parse_cephalopodian(
+                "1
+ +
+"
+            ),
+            Err(ParseError::EmptyColumn { column: 1 })
+        );
+        assert_eq!(
+            Worksheet::solve_cephalopodian_worksheet(
+                "12
+34
+  *
+"
+            ),
+            Err(WorksheetError::Parse(ParseError::EmptyColumn { column: 2 }))
+        );
+        assert_eq!(
+            Worksheet::parse_cephalopodian(
+                "+
+"
+            ),
+            Err(ParseError::EmptyColumn { column: 0 })
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Worksheet::parse("1 2\n3 4\n+ x\n"),
+            Err(ParseError::InvalidOperator { columns: 2..=2 })
+        );
+        assert_eq!(
+            Worksheet::parse("12 3\n45 6\n+* +\n"),
+            Err(ParseError::InvalidOperator { columns: 0..=1 })
+        );
+        assert_eq!(
+            Worksheet::parse("1 2\n3\n+ *\n"),
+            Err(ParseError::UnevenRow {
+                line: 1,
+                numbers: 1,
+                expected: 2
+            })
+        );
+        assert_eq!(
+            Worksheet::solve_worksheet("1 2 3\n+ *\n"),
+            Err(WorksheetError::Parse(ParseError::UnevenRow {
+                line: 0,
+                numbers: 3,
+                expected: 2
+            }))
+        );
+        assert_eq!(
+            Worksheet::parse("+ *\n"),
+            Err(ParseError::EmptyProblem { columns: 0..=0 })
+        );
+        assert_eq!(Worksheet::solve_worksheet(""), Ok(0));
+
+        assert_eq!(
+            Worksheet::parse("1 2 é\n3 4 5\n+ * x\n"),
+            Err(ParseError::InvalidNumber {
+                line: 0,
+                columns: 4..=4
+            })
+        );
+
+        // Misparsed numbers are reported instead of shifting the next ones to another problem
+        assert_eq!(
+            Worksheet::parse("1 2x 3\n4 5 6\n+ * +\n"),
+            Err(ParseError::InvalidNumber {
+                line: 0,
+                columns: 2..=3
+            })
+        );
+        assert_eq!(
+            Worksheet::report("1 2\n3 18446744073709551616\n+ *\n"),
+            Err(ParseError::InvalidNumber {
+                line: 1,
+                columns: 2..=21
+            })
+        );
+    }
+
+    const EXPRESSION_INPUT: &str = "2 10 2 100
+3  4 3   5
+4  2 2   2
+5  3 1   3
++*+ -/* ^^^ <+*
+";
+
+    #[test]
+    fn test_expressions() {
+        let expressions =
+            Worksheet::parse_expressions(EXPRESSION_INPUT, Evaluation::Precedence).unwrap();
+        assert_eq!(
+            expressions
+                .iter()
+                .map(|expression| expression.to_string())
+                .collect::<Vec<_>>(),
+            [
+                "((2 + (3 * 4)) + 5)",
+                "(10 - ((4 / 2) * 3))",
+                "(2 ^ (3 ^ (2 ^ 1)))",
+                "(100 < (5 + (2 * 3)))"
+            ]
+        );
+        assert_eq!(
+            Worksheet::solve_expressions(EXPRESSION_INPUT, Evaluation::Precedence),
+            Ok(19 + 4 + 512 + 11)
+        );
+
+        let expressions =
+            Worksheet::parse_expressions(EXPRESSION_INPUT, Evaluation::LeftToRight).unwrap();
+        assert_eq!(expressions[2].to_string(), "(((2 ^ 3) ^ 2) ^ 1)");
+        assert_eq!(
+            Worksheet::solve_expressions(EXPRESSION_INPUT, Evaluation::LeftToRight),
+            Ok(25 + 9 + 64 + 21)
+        );
+
+        // A single operator applies to every gap
+        for evaluation in [Evaluation::Precedence, Evaluation::LeftToRight] {
+            assert_eq!(Worksheet::solve_expressions(INPUT, evaluation), Ok(4277556));
+        }
+        // A single `^` folds from left to right like in `Worksheet::parse`, while one `^`
+        // per gap is right associative when following precedence
+        let input = "2\n3\n3\n^\n";
+        for evaluation in [Evaluation::Precedence, Evaluation::LeftToRight] {
+            assert_eq!(Worksheet::solve_expressions(input, evaluation), Ok(512));
+        }
+        assert_eq!(Worksheet::solve_worksheet(input), Ok(512));
+        assert_eq!(
+            Worksheet::solve_expressions("2\n3\n3\n^^\n", Evaluation::Precedence),
+            Ok(1 << 27)
+        );
+        assert_eq!(
+            Worksheet::solve_expressions("2\n3\n3\n^^\n", Evaluation::LeftToRight),
+            Ok(512)
+        );
+
+        assert_eq!(
+            Worksheet::solve_expressions("2\n3\n9\n^-\n", Evaluation::Precedence),
+            Err(WorksheetError::Problem {
+                problem: 0,
+                error: ArithmeticError::Overflow
+            })
+        );
+    }
+
+    #[test]
+    fn test_expression_parse_errors() {
+        assert_eq!(
+            Worksheet::parse_expressions("1 2\n3 4\n5 6\n+* +x\n", Evaluation::Precedence),
+            Err(ParseError::InvalidOperator { columns: 2..=4 })
+        );
+        assert_eq!(
+            Worksheet::parse_expressions("1\n3\n5\n+*+\n", Evaluation::Precedence),
+            Err(ParseError::OperatorCount {
+                columns: 0..=2,
+                operators: 3,
+                expected: 2
+            })
+        );
+        assert_eq!(
+            Worksheet::solve_expressions("+*\n", Evaluation::LeftToRight),
+            Err(WorksheetError::Parse(ParseError::EmptyProblem {
+                columns: 0..=1
+            }))
+        );
+    }
+
+    #[test]
+    fn test_arithmetic_errors() {
+        assert_eq!(
+            Worksheet::solve_worksheet("5 7\n6 0\n- %\n"),
+            Err(WorksheetError::Problem {
+                problem: 0,
+                error: ArithmeticError::Overflow
+            })
+        );
+        assert_eq!(
+            Worksheet::solve_worksheet("6 7\n5 0\n- %\n"),
+            Err(WorksheetError::Problem {
+                problem: 1,
+                error: ArithmeticError::DivisionByZero
+            })
+        );
+        assert_eq!(
+            Worksheet::solve_worksheet("6 7\n5 0\n- /\n"),
+            Err(WorksheetError::Problem {
+                problem: 1,
+                error: ArithmeticError::DivisionByZero
+            })
+        );
+        assert_eq!(
+            Worksheet::solve_worksheet("64 2\n23 3\n^ +\n"),
+            Err(WorksheetError::Problem {
+                problem: 0,
+                error: ArithmeticError::Overflow
+            })
+        );
+        assert_eq!(
+            Worksheet::solve_worksheet("18446744073709551615 1\n1 1\n* +\n"),
+            Err(WorksheetError::TotalOverflow)
+        );
+        assert_eq!(
+            Worksheet::solve_worksheet("18446744073709551615\n1\n+\n"),
+            Err(WorksheetError::Problem {
+                problem: 0,
+                error: ArithmeticError::Overflow
+            })
+        );
     }
 }
@@ -1,53 +1,285 @@
-use std::collections::{HashMap, HashSet};
-
-#[derive(Debug)]
-pub struct TachyonManifold<'a> {
-    /// Number of characters in each line of the manifold,
-    /// does not account for the new line characters.
-    width: usize,
-    manifold: &'a [u8],
-    starting_beam: usize,
+use std::{fmt::Display, ops::RangeInclusive};
+
+use crate::{big_uint::BigUint, grid::Grid};
+
+mod optics;
+
+pub use optics::{Direction, OpticsConfig, Trace, TraceError};
+
+const EMPTY: u8 = b'.';
+const SOURCE: u8 = b'S';
+const SPLITTER: u8 = b'^';
+const MIRROR: u8 = b'/';
+const BACK_MIRROR: u8 = b'\\';
+const ABSORBER: u8 = b'#';
+const LEFT_DEFLECTOR: u8 = b'<';
+const RIGHT_DEFLECTOR: u8 = b'>';
+const TILES: [u8; 8] = [
+    EMPTY,
+    SOURCE,
+    SPLITTER,
+    MIRROR,
+    BACK_MIRROR,
+    ABSORBER,
+    LEFT_DEFLECTOR,
+    RIGHT_DEFLECTOR,
+];
+
+/// Manifold where each source `S` sends a beam down, starting on the row under it
+///
+/// Counting and reports only follow splitters `^`, going through mirrors, absorbers and
+/// deflectors as if they were empty space. Only [`TachyonManifold::trace`] handles those.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TachyonManifold {
+    manifold: Grid<u8>,
+    /// Positions of the sources, in reading order
+    sources: Vec<(usize, usize)>,
 }
 
-impl<'a> TachyonManifold<'a> {
-    pub fn new(manifold: &'a [u8]) -> TachyonManifold<'a> {
-        let Some(first_line_break) = manifold.iter().position(|c| *c == b'\n') else {
-            unreachable!("Input will always have multiple lines.");
-        };
-        let Some(starting_beam) = manifold[..first_line_break].iter().position(|c| *c == b'S')
-        else {
-            unreachable!("Input will always have a start on the first line.");
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManifoldError {
+    /// Manifold has no source `S`, so no beam ever enters it
+    NoSource,
+    /// Line ends with `\r\n`, whose `\r` would be read as an extra tile
+    CrlfLineEnding {
+        row: usize,
+    },
+    /// Row does not have as many tiles as the first one
+    RaggedRow {
+        row: usize,
+        width: usize,
+        expected: usize,
+    },
+    /// Unknown tile, such as a `\r` in the middle of a line
+    InvalidTile {
+        row: usize,
+        column: usize,
+        tile: char,
+    },
+    OutOfBounds {
+        row: usize,
+        column: usize,
+    },
+}
+
+/// Path of the beams through the manifold, with positions given as `(row, column)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BeamReport {
+    distribution: Vec<Vec<(usize, u64)>>,
+    splitters_hit: Vec<(usize, usize)>,
+    splitters_missed: Vec<(usize, usize)>,
+}
+
+impl TachyonManifold {
+    /// Parses a manifold with lines ending with `\n`
+    pub fn new(manifold: &[u8]) -> Result<TachyonManifold, ManifoldError> {
+        let mut lines = manifold.split(|c| *c == b'\n').collect::<Vec<_>>();
+        while lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+
+        let expected = lines.first().map_or(0, |line| line.len());
+        for (row, line) in lines.iter().enumerate() {
+            if line.ends_with(b"\r") {
+                return Err(ManifoldError::CrlfLineEnding { row });
+            }
+            if line.len() != expected {
+                return Err(ManifoldError::RaggedRow {
+                    row,
+                    width: line.len(),
+                    expected,
+                });
+            }
+            if let Some((column, tile)) = line
+                .iter()
+                .enumerate()
+                .find(|(_, tile)| !TILES.contains(tile))
+            {
+                return Err(ManifoldError::InvalidTile {
+                    row,
+                    column,
+                    tile: char::from(*tile),
+                });
+            }
+        }
+
+        let manifold = Grid::from_rows(lines);
+        let sources = manifold
+            .enumerate()
+            .filter(|(_, tile)| **tile == SOURCE)
+            .map(|(position, _)| position)
+            .collect::<Vec<_>>();
+        if sources.is_empty() {
+            return Err(ManifoldError::NoSource);
+        }
+
+        Ok(TachyonManifold { manifold, sources })
+    }
+
+    pub fn sources(&self) -> &[(usize, usize)] {
+        &self.sources
+    }
+
+    pub fn tile(&self, position: (usize, usize)) -> Option<u8> {
+        self.manifold.get(position).copied()
+    }
+
+    /// Replaces the tile at `position`, returning the previous one
+    ///
+    /// Fails without changing anything if the tile is unknown,
+    /// or if it would remove the last source.
+    pub fn set_tile(&mut self, position: (usize, usize), tile: u8) -> Result<u8, ManifoldError> {
+        let (row, column) = position;
+        if !TILES.contains(&tile) {
+            return Err(ManifoldError::InvalidTile {
+                row,
+                column,
+                tile: char::from(tile),
+            });
+        }
+        let Some(previous) = self.manifold.get(position).copied() else {
+            return Err(ManifoldError::OutOfBounds { row, column });
         };
-        let width = first_line_break;
 
-        TachyonManifold {
-            width,
-            manifold: &manifold[(first_line_break + 1)..],
-            starting_beam,
+        match (previous == SOURCE, tile == SOURCE) {
+            (true, false) if self.sources.len() == 1 => return Err(ManifoldError::NoSource),
+            (true, false) => self.sources.retain(|source| *source != position),
+            (false, true) => {
+                let index = self.sources.partition_point(|source| *source < position);
+                self.sources.insert(index, position);
+            }
+            _ => (),
         }
+        self.manifold[position] = tile;
+
+        Ok(previous)
     }
 
+    /// Counts the splitters hit by a beam, with the beams of a row stored as a bitset
+    ///
+    /// Other optical elements are ignored, unlike in [`TachyonManifold::trace`].
     pub fn count_splits(&self) -> u64 {
-        let TachyonManifold {
-            width,
-            manifold,
-            starting_beam,
-        } = self;
+        let manifold = &self.manifold;
+        let width = manifold.width();
+        let words = width.div_ceil(64);
+
+        let mut splits = 0;
+
+        let mut beams = vec![0u64; words];
+        let mut beams_b = vec![0u64; words];
+        let mut hits = vec![0u64; words];
+        let mut sources = self.sources.iter().peekable();
+        // Words that may hold a beam
+        let mut active: Option<(usize, usize)> = None;
+
+        for (row, line) in manifold.rows().enumerate() {
+            if let Some((low, high)) = active {
+                // Beams move by at most one column, so by at most one word
+                let low_out = low.saturating_sub(1);
+                let high_out = (high + 1).min(words - 1);
+
+                for word in low_out..=high_out {
+                    hits[word] = beams[word] & splitter_mask(line, word);
+                    splits += u64::from(hits[word].count_ones());
+                }
+                for word in low_out..=high_out {
+                    let hit = |word: Option<usize>| {
+                        word.filter(|word| (low_out..=high_out).contains(word))
+                            .map_or(0, |word| hits[word])
+                    };
+                    beams_b[word] = (beams[word] ^ hits[word])
+                        | (hits[word] << 1)
+                        | (hits[word] >> 1)
+                        | (hit(word.checked_sub(1)) >> 63)
+                        | (hit(Some(word + 1)) << 63);
+                    beams[word] = 0;
+                }
+                // Beams past the right edge are dropped
+                if !width.is_multiple_of(64) {
+                    beams_b[words - 1] &= (1 << (width % 64)) - 1;
+                }
+                std::mem::swap(&mut beams, &mut beams_b);
+
+                let mut nonempty = (low_out..=high_out).filter(|word| beams[*word] != 0);
+                active = nonempty
+                    .next()
+                    .map(|first| (first, nonempty.next_back().unwrap_or(first)));
+            }
+
+            // Beams of the sources start on the next row
+            while let Some((_, column)) = sources.next_if(|(source_row, _)| *source_row == row) {
+                let word = column / 64;
+                beams[word] |= 1 << (column % 64);
+                active = Some(
+                    active.map_or((word, word), |(low, high)| (low.min(word), high.max(word))),
+                );
+            }
+        }
+
+        splits
+    }
+
+    /// Counts the timelines, failing if their number does not fit in an `u64`
+    ///
+    /// Other optical elements than splitters are ignored, unlike in [`TachyonManifold::trace`].
+    pub fn count_timelines(&self) -> Result<u64, TimelineError> {
+        self.count_timelines_as()
+    }
+
+    /// Counts the timelines, failing if their number does not fit in an `u128`,
+    /// ignoring other optical elements than splitters like [`TachyonManifold::count_timelines`]
+    pub fn count_timelines_u128(&self) -> Result<u128, TimelineError> {
+        self.count_timelines_as()
+    }
+
+    /// Counts the timelines in arbitrary precision, as they double with every row of splitters,
+    /// ignoring other optical elements like [`TachyonManifold::count_timelines`]
+    pub fn count_timelines_exact(&self) -> BigUint {
+        self.count_timelines_as()
+            .expect("Arbitrary precision never overflows")
+    }
+
+    /// Each source starts a timeline, and each split adds one
+    fn count_timelines_as<C: Count>(&self) -> Result<C, TimelineError> {
+        let mut timelines = C::default();
+        for (row, _) in self.sources.iter() {
+            if !timelines.try_add(&C::one()) {
+                return Err(TimelineError::Overflow { row: *row });
+            }
+        }
+
+        self.propagate(|row, splits, _, _| {
+            for (_, count) in splits {
+                if !timelines.try_add(count) {
+                    return Err(TimelineError::Overflow { row });
+                }
+            }
+            Ok(())
+        })?;
+        Ok(timelines)
+    }
+
+    /// Same as [`TachyonManifold::count_splits`], but with the beams stored in a `HashSet`
+    /// instead of a bitset, to check the bitset against
+    #[cfg(any(test, feature = "reference"))]
+    pub fn count_splits_hashed(&self) -> u64 {
+        use std::collections::HashSet;
+
+        let width = self.manifold.width();
 
         let mut splits = 0;
 
-        let mut beams = HashSet::new();
-        beams.insert(*starting_beam);
+        let mut beams = HashSet::<usize>::new();
         let mut beams_b = HashSet::new();
 
-        for line in manifold.chunks(width + 1) {
+        for (row, line) in self.manifold.rows().enumerate() {
             for beam in beams.drain() {
-                if line[beam] == b'^' {
+                if line[beam] == SPLITTER {
                     splits += 1;
                     if let Some(left) = beam.checked_sub(1) {
                         beams_b.insert(left);
                     }
-                    if let Some(right) = Some(beam + 1).filter(|beam| beam < width) {
+                    if let Some(right) = Some(beam + 1).filter(|beam| *beam < width) {
                         beams_b.insert(right);
                     }
                 } else {
@@ -55,57 +287,306 @@ impl<'a> TachyonManifold<'a> {
                 }
             }
             std::mem::swap(&mut beams, &mut beams_b);
+
+            for (_, column) in self.sources.iter().filter(|(source, _)| *source == row) {
+                beams.insert(*column);
+            }
         }
 
         splits
     }
 
-    pub fn count_timelines(&self) -> u64 {
-        let TachyonManifold {
-            width,
-            manifold,
-            starting_beam,
-        } = self;
+    /// Same as [`TachyonManifold::count_timelines`], but with the timelines of each beam
+    /// stored in a `HashMap` instead of a dense row of counts, to check the dense row against
+    #[cfg(any(test, feature = "reference"))]
+    pub fn count_timelines_hashed(&self) -> Result<u64, TimelineError> {
+        use std::collections::HashMap;
+
+        fn add_beam(
+            beams: &mut HashMap<usize, u64>,
+            beam: usize,
+            count: u64,
+            row: usize,
+        ) -> Result<(), TimelineError> {
+            let entry = beams.entry(beam).or_insert(0);
+            *entry = entry
+                .checked_add(count)
+                .ok_or(TimelineError::Overflow { row })?;
+            Ok(())
+        }
+
+        let width = self.manifold.width();
 
-        let mut timelines = 1;
+        let mut timelines = self.sources.len() as u64;
 
-        let mut beams = HashMap::new();
-        beams.insert(*starting_beam, 1);
+        let mut beams = HashMap::<usize, u64>::new();
         let mut beams_b = HashMap::new();
 
-        for line in manifold.chunks(width + 1) {
+        for (row, line) in self.manifold.rows().enumerate() {
             for (beam, count) in beams.drain() {
-                if line[beam] == b'^' {
-                    timelines += count;
+                if line[beam] == SPLITTER {
+                    timelines = timelines
+                        .checked_add(count)
+                        .ok_or(TimelineError::Overflow { row })?;
                     if let Some(left) = beam.checked_sub(1) {
-                        beams_b
-                            .entry(left)
-                            .and_modify(|entry_count: &mut u64| *entry_count += count)
-                            .or_insert(count);
+                        add_beam(&mut beams_b, left, count, row)?;
                     }
-                    if let Some(right) = Some(beam + 1).filter(|beam| beam < width) {
-                        beams_b
-                            .entry(right)
-                            .and_modify(|entry_count: &mut u64| *entry_count += count)
-                            .or_insert(count);
+                    if let Some(right) = Some(beam + 1).filter(|beam| *beam < width) {
+                        add_beam(&mut beams_b, right, count, row)?;
                     }
                 } else {
-                    beams_b
-                        .entry(beam)
-                        .and_modify(|entry_count: &mut u64| *entry_count += count)
-                        .or_insert(count);
+                    add_beam(&mut beams_b, beam, count, row)?;
                 }
             }
             std::mem::swap(&mut beams, &mut beams_b);
+
+            for (_, column) in self.sources.iter().filter(|(source, _)| *source == row) {
+                add_beam(&mut beams, *column, 1, row)?;
+            }
+        }
+
+        Ok(timelines)
+    }
+
+    /// Follows the beams down through the splitters only, going through other
+    /// optical elements, which [`TachyonManifold::trace`] follows instead
+    pub fn beam_report(&self) -> Result<BeamReport, TimelineError> {
+        let mut distribution = vec![];
+        let mut splitters_hit = vec![];
+
+        self.propagate(|row, splits, beams: &[u64], columns| {
+            splitters_hit.extend(splits.iter().map(|(column, _)| (row, *column)));
+            distribution.push(
+                columns
+                    .filter(|column| beams[*column] != 0)
+                    .map(|column| (column, beams[column]))
+                    .collect(),
+            );
+            Ok(())
+        })?;
+
+        let splitters_missed = self
+            .manifold
+            .enumerate()
+            .filter(|(_, tile)| **tile == SPLITTER)
+            .map(|(position, _)| position)
+            .filter(|splitter| splitters_hit.binary_search(splitter).is_err())
+            .collect();
+
+        Ok(BeamReport {
+            distribution,
+            splitters_hit,
+            splitters_missed,
+        })
+    }
+
+    /// Moves the timelines down the manifold, with the number of timelines of each column
+    /// stored in a `Vec`, only visiting the columns between the leftmost and rightmost beams
+    ///
+    /// After each row, `on_row` gets the index of the row, the columns where beams were split
+    /// with their timelines, and the timelines of each column going down to the next row,
+    /// sources included, all 0 outside of the given columns.
+    fn propagate<C: Count>(
+        &self,
+        mut on_row: impl FnMut(
+            usize,
+            &[(usize, C)],
+            &[C],
+            RangeInclusive<usize>,
+        ) -> Result<(), TimelineError>,
+    ) -> Result<(), TimelineError> {
+        let width = self.manifold.width();
+
+        let mut beams = vec![C::default(); width];
+        let mut beams_b = vec![C::default(); width];
+        let mut splits = vec![];
+        let mut sources = self.sources.iter().peekable();
+        // Columns between the leftmost and rightmost beams, empty when there are none
+        let (mut low, mut high) = (usize::MAX, 0);
+
+        for (row, line) in self.manifold.rows().enumerate() {
+            splits.clear();
+            let (mut next_low, mut next_high) = (usize::MAX, 0);
+            let mut add = |beams: &mut [C], column: usize, count: &C| {
+                next_low = next_low.min(column);
+                next_high = next_high.max(column);
+                if beams[column].try_add(count) {
+                    Ok(())
+                } else {
+                    Err(TimelineError::Overflow { row })
+                }
+            };
+
+            for beam in low..=high {
+                let count = std::mem::take(&mut beams[beam]);
+                if count.is_zero() {
+                    continue;
+                }
+                if line[beam] == SPLITTER {
+                    if let Some(left) = beam.checked_sub(1) {
+                        add(&mut beams_b, left, &count)?;
+                    }
+                    if beam + 1 < width {
+                        add(&mut beams_b, beam + 1, &count)?;
+                    }
+                    splits.push((beam, count));
+                } else {
+                    add(&mut beams_b, beam, &count)?;
+                }
+            }
+
+            // Beams of the sources start on the next row
+            while let Some((_, column)) = sources.next_if(|(source_row, _)| *source_row == row) {
+                add(&mut beams_b, *column, &C::one())?;
+            }
+            std::mem::swap(&mut beams, &mut beams_b);
+
+            on_row(row, &splits, &beams, next_low..=next_high)?;
+            (low, high) = (next_low, next_high);
+        }
+
+        Ok(())
+    }
+}
+
+/// Number of timelines, added with overflow checks
+trait Count: Clone + Default {
+    fn one() -> Self;
+
+    fn is_zero(&self) -> bool;
+
+    /// Adds `other`, returning `false` if the result does not fit
+    fn try_add(&mut self, other: &Self) -> bool;
+}
+
+impl Count for u64 {
+    fn one() -> Self {
+        1
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == 0
+    }
+
+    fn try_add(&mut self, other: &Self) -> bool {
+        self.checked_add(*other).map(|sum| *self = sum).is_some()
+    }
+}
+
+impl Count for u128 {
+    fn one() -> Self {
+        1
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == 0
+    }
+
+    fn try_add(&mut self, other: &Self) -> bool {
+        self.checked_add(*other).map(|sum| *self = sum).is_some()
+    }
+}
+
+impl Count for BigUint {
+    fn one() -> Self {
+        BigUint::from(1u64)
+    }
+
+    fn is_zero(&self) -> bool {
+        BigUint::is_zero(self)
+    }
+
+    fn try_add(&mut self, other: &Self) -> bool {
+        *self += other;
+        true
+    }
+}
+
+/// Bits of the splitters of the `word`-th group of 64 columns of `line`
+fn splitter_mask(line: &[u8], word: usize) -> u64 {
+    line[(word * 64)..((word + 1) * 64).min(line.len())]
+        .iter()
+        .enumerate()
+        .fold(0, |mask, (bit, tile)| {
+            mask | (u64::from(*tile == SPLITTER) << bit)
+        })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimelineError {
+    /// Number of timelines no longer fits after the given row
+    Overflow { row: usize },
+}
+
+impl BeamReport {
+    /// Number of timelines on each column holding a beam going down,
+    /// in increasing column order, for each row
+    pub fn distribution(&self) -> &[Vec<(usize, u64)>] {
+        &self.distribution
+    }
+
+    /// Splitters reached by at least one beam, in reading order
+    pub fn splitters_hit(&self) -> &[(usize, usize)] {
+        &self.splitters_hit
+    }
+
+    /// Splitters no beam ever reached, in reading order
+    pub fn splitters_missed(&self) -> &[(usize, usize)] {
+        &self.splitters_missed
+    }
+
+    /// Columns the beams leave the bottom of the manifold from, with their number of timelines
+    pub fn exits(&self) -> &[(usize, u64)] {
+        self.distribution
+            .last()
+            .expect("Manifold always has a row with a source")
+    }
+}
+
+impl Display for TimelineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Overflow { row } => write!(f, "Number of timelines overflows on row {row}."),
+        }
+    }
+}
+
+impl std::error::Error for TimelineError {}
+
+impl Display for ManifoldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoSource => write!(f, "Manifold has no source."),
+            Self::CrlfLineEnding { row } => {
+                write!(f, "Row {row} ends with \\r\\n instead of \\n.")
+            }
+            Self::RaggedRow {
+                row,
+                width,
+                expected,
+            } => write!(f, "Row {row} has {width} tiles instead of {expected}."),
+            Self::InvalidTile { row, column, tile } => {
+                write!(f, "Invalid tile {tile:?} on row {row}, column {column}.")
+            }
+            Self::OutOfBounds { row, column } => {
+                write!(f, "Row {row}, column {column} is outside of the manifold.")
+            }
         }
+    }
+}
 
-        timelines
+impl std::error::Error for ManifoldError {}
+
+impl Display for TachyonManifold {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.manifold)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::fixtures;
 
     const INPUT: &[u8] = b".......S.......
 ...............
@@ -127,11 +608,345 @@ mod tests {
 
     #[test]
     fn test_splits() {
-        assert_eq!(TachyonManifold::new(INPUT).count_splits(), 21);
+        assert_eq!(TachyonManifold::new(INPUT).unwrap().count_splits(), 21);
     }
 
     #[test]
     fn test_timelines() {
-        assert_eq!(TachyonManifold::new(INPUT).count_timelines(), 40);
+        assert_eq!(
+            TachyonManifold::new(INPUT).unwrap().count_timelines(),
+            Ok(40)
+        );
+    }
+
+    #[test]
+    fn test_dense_matches_hashed() {
+        let manifold = TachyonManifold::new(INPUT).unwrap();
+        assert_eq!(manifold.count_splits_hashed(), 21);
+        assert_eq!(manifold.count_timelines_hashed(), Ok(40));
+
+        for (width, seed) in [
+            (1, 1),
+            (2, 2),
+            (63, 3),
+            (64, 4),
+            (65, 5),
+            (130, 6),
+            (300, 7),
+        ] {
+            for start in [0, width / 2, width - 1] {
+                let manifold =
+                    TachyonManifold::new(&fixtures::sparse_manifold(width, 60, start, 10, seed))
+                        .unwrap();
+                assert_eq!(
+                    manifold.count_splits(),
+                    manifold.count_splits_hashed(),
+                    "{width} {start}"
+                );
+                assert_eq!(
+                    manifold.count_timelines(),
+                    manifold.count_timelines_hashed(),
+                    "{width} {start}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_trace_splitters_only() {
+        let manifold = TachyonManifold::new(INPUT).unwrap();
+        for wrap_around in [false, true] {
+            let trace = manifold.trace(&OpticsConfig { wrap_around });
+            assert_eq!(
+                trace.splitters_hit(),
+                manifold.beam_report().unwrap().splitters_hit()
+            );
+            assert_eq!(trace.timelines(), Ok(40));
+            assert!(trace.absorbed().is_empty());
+            assert_eq!(
+                trace
+                    .exits()
+                    .iter()
+                    .map(|(position, _)| *position)
+                    .collect::<Vec<_>>(),
+                manifold
+                    .beam_report()
+                    .unwrap()
+                    .exits()
+                    .iter()
+                    .map(|(column, _)| (15, *column))
+                    .collect::<Vec<_>>()
+            );
+        }
+
+        // The left beam falls off the side, unless it wraps around
+        let manifold = TachyonManifold::new(b"S..\n^..\n...\n").unwrap();
+        let trace = manifold.trace(&OpticsConfig::default());
+        assert_eq!(trace.timelines(), Ok(manifold.count_timelines().unwrap()));
+        assert_eq!(
+            trace.exits(),
+            [((1, 0), Direction::Left), ((2, 1), Direction::Down)]
+        );
+        let trace = manifold.trace(&OpticsConfig { wrap_around: true });
+        assert_eq!(trace.timelines(), Ok(2));
+        assert_eq!(
+            trace.exits(),
+            [((2, 1), Direction::Down), ((2, 2), Direction::Down)]
+        );
+
+        // Same on the right side
+        let manifold = TachyonManifold::new(b"..S\n..^\n...\n").unwrap();
+        let trace = manifold.trace(&OpticsConfig::default());
+        assert_eq!(trace.timelines(), Ok(2));
+        assert_eq!(
+            trace.exits(),
+            [((1, 2), Direction::Right), ((2, 1), Direction::Down)]
+        );
+
+        // Through the bottom for a splitter on the corner of the last row
+        let manifold = TachyonManifold::new(b"S..\n^..\n").unwrap();
+        let trace = manifold.trace(&OpticsConfig::default());
+        assert_eq!(trace.timelines(), Ok(2));
+        assert_eq!(trace.exits(), [((1, 0), Direction::Down)]);
+    }
+
+    #[test]
+    fn test_trace_optics() {
+        // Mirror sending the beam sideways, along the row, into an absorber
+        let manifold = TachyonManifold::new(b"S....\n\\...#\n.....\n").unwrap();
+        let trace = manifold.trace(&OpticsConfig::default());
+        assert_eq!(trace.absorbed(), [(1, 4)]);
+        assert!(trace.exits().is_empty());
+        assert_eq!(trace.timelines(), Ok(1));
+
+        // Split beams carry on diagonally, skipping the mirrors next to the splitter
+        let manifold = TachyonManifold::new(b"..S..\n./^\\.\n.....\n").unwrap();
+        let trace = manifold.trace(&OpticsConfig::default());
+        assert_eq!(trace.splitters_hit(), [(1, 2)]);
+        assert_eq!(trace.timelines(), Ok(2));
+        assert_eq!(
+            trace.exits(),
+            [((2, 1), Direction::Down), ((2, 3), Direction::Down)]
+        );
+
+        // Deflector sending the beam out of the left side, or around the manifold and into
+        // its own side when wrapping, or into the back of the other deflector
+        let manifold = TachyonManifold::new(b"..S..\n..<..\n.....\n").unwrap();
+        let trace = manifold.trace(&OpticsConfig::default());
+        assert_eq!(trace.exits(), [((1, 0), Direction::Left)]);
+        assert_eq!(
+            manifold
+                .trace(&OpticsConfig { wrap_around: true })
+                .timelines(),
+            Err(TraceError::Loop {
+                position: (1, 1),
+                direction: Direction::Left
+            })
+        );
+        let manifold = TachyonManifold::new(b"..S..\n..<.>\n.....\n").unwrap();
+        let trace = manifold.trace(&OpticsConfig { wrap_around: true });
+        assert_eq!(trace.absorbed(), [(1, 4)]);
+        assert_eq!(trace.timelines(), Ok(1));
+
+        // Only the trace stops at the absorber above the splitter
+        let manifold = TachyonManifold::new(b"..S..\n..#..\n..^..\n").unwrap();
+        let trace = manifold.trace(&OpticsConfig::default());
+        assert!(trace.splitters_hit().is_empty());
+        assert_eq!(trace.timelines(), Ok(1));
+        assert_eq!(manifold.count_splits(), 1);
+        assert_eq!(manifold.count_timelines(), Ok(2));
+        assert_eq!(manifold.beam_report().unwrap().splitters_hit(), [(2, 2)]);
+    }
+
+    #[test]
+    fn test_trace_loop() {
+        // Both split beams end up going around the mirrors forever
+        let manifold = TachyonManifold::new(b"..S..\n./.\\.\n..^..\n.\\./.\n").unwrap();
+        let trace = manifold.trace(&OpticsConfig::default());
+        assert!(matches!(trace.timelines(), Err(TraceError::Loop { .. })));
+        assert_eq!(trace.splitters_hit(), [(2, 2)]);
+        assert!(trace.exits().is_empty());
+        assert!(trace.absorbed().is_empty());
+        assert_eq!(manifold.count_splits(), 1);
+    }
+
+    #[test]
+    fn test_timelines_overflow() {
+        // Every path splits on every row of a full triangle, giving `2^rows` timelines
+        let manifold = TachyonManifold::new(&fixtures::triangle_manifold(63)).unwrap();
+        assert_eq!(manifold.count_timelines(), Ok(1 << 63));
+        assert_eq!(manifold.count_timelines_hashed(), Ok(1 << 63));
+        assert_eq!(
+            manifold.trace(&OpticsConfig::default()).timelines(),
+            Ok(1 << 63)
+        );
+
+        let manifold = TachyonManifold::new(&fixtures::triangle_manifold(64)).unwrap();
+        assert_eq!(
+            manifold.count_timelines(),
+            Err(TimelineError::Overflow { row: 128 })
+        );
+        assert_eq!(
+            manifold.count_timelines_hashed(),
+            Err(TimelineError::Overflow { row: 128 })
+        );
+        assert_eq!(manifold.count_timelines_u128(), Ok(1 << 64));
+        // Timelines of a single column only overflow once they reach `C(68, 34)`
+        assert!(manifold.beam_report().is_ok());
+        assert_eq!(
+            TachyonManifold::new(&fixtures::triangle_manifold(70))
+                .unwrap()
+                .beam_report(),
+            Err(TimelineError::Overflow { row: 136 })
+        );
+        assert!(matches!(
+            manifold.trace(&OpticsConfig::default()).timelines(),
+            Err(TraceError::Overflow { .. })
+        ));
+
+        let manifold = TachyonManifold::new(&fixtures::triangle_manifold(127)).unwrap();
+        assert_eq!(manifold.count_timelines_u128(), Ok(1 << 127));
+        let manifold = TachyonManifold::new(&fixtures::triangle_manifold(128)).unwrap();
+        assert_eq!(
+            manifold.count_timelines_u128(),
+            Err(TimelineError::Overflow { row: 256 })
+        );
+        assert_eq!(
+            manifold.count_timelines_exact(),
+            BigUint::from(1u128 << 64).pow(2)
+        );
+
+        let manifold = TachyonManifold::new(&fixtures::triangle_manifold(300)).unwrap();
+        assert_eq!(
+            manifold.count_timelines_exact(),
+            BigUint::from(2u64).pow(300)
+        );
+        assert_eq!(manifold.count_splits(), 300 * 301 / 2);
+    }
+
+    #[test]
+    fn test_beam_report() {
+        let report = TachyonManifold::new(INPUT).unwrap().beam_report().unwrap();
+        assert_eq!(report.distribution().len(), 16);
+        assert_eq!(report.distribution()[0], [(7, 1)]);
+        assert_eq!(report.distribution()[2], [(6, 1), (8, 1)]);
+        assert_eq!(report.distribution()[4], [(5, 1), (7, 2), (9, 1)]);
+        assert_eq!(report.splitters_hit().len(), 21);
+        assert_eq!(report.splitters_hit()[0], (2, 7));
+        assert_eq!(report.splitters_missed(), [(14, 9)]);
+        assert_eq!(
+            report.exits(),
+            [
+                (0, 1),
+                (2, 2),
+                (4, 10),
+                (6, 11),
+                (8, 11),
+                (10, 2),
+                (11, 1),
+                (12, 1),
+                (14, 1)
+            ]
+        );
+        assert_eq!(
+            report.exits().iter().map(|(_, count)| count).sum::<u64>(),
+            40
+        );
+    }
+
+    #[test]
+    fn test_multiple_sources() {
+        let manifold = TachyonManifold::new(b"..S..\n.....\n..^.S\n.....\n.^...\n").unwrap();
+        assert_eq!(manifold.sources(), [(0, 2), (2, 4)]);
+        assert_eq!(manifold.count_splits(), 2);
+        assert_eq!(manifold.count_splits_hashed(), 2);
+        assert_eq!(manifold.count_timelines(), Ok(4));
+        assert_eq!(manifold.count_timelines_hashed(), Ok(4));
+        assert_eq!(manifold.count_timelines_exact(), BigUint::from(4u64));
+        let report = manifold.beam_report().unwrap();
+        assert_eq!(report.splitters_hit(), [(2, 2), (4, 1)]);
+        assert_eq!(report.exits(), [(0, 1), (2, 1), (3, 1), (4, 1)]);
+        assert_eq!(manifold.trace(&OpticsConfig::default()).timelines(), Ok(4));
+
+        let mut manifold =
+            TachyonManifold::new(&fixtures::sparse_manifold(130, 60, 65, 10, 8)).unwrap();
+        for position in [(7, 3), (21, 64), (40, 129)] {
+            manifold.set_tile(position, b'S').unwrap();
+            assert_eq!(manifold.count_splits(), manifold.count_splits_hashed());
+            assert_eq!(
+                manifold.count_timelines(),
+                manifold.count_timelines_hashed(),
+                "{position:?}"
+            );
+        }
+        assert_eq!(manifold.sources().len(), 4);
+    }
+
+    #[test]
+    fn test_set_tile() {
+        let mut manifold = TachyonManifold::new(INPUT).unwrap();
+        assert_eq!(manifold.set_tile((2, 7), b'.'), Ok(b'^'));
+        assert_eq!(manifold.tile((2, 7)), Some(b'.'));
+        assert_eq!(manifold.count_splits(), 6);
+        assert_eq!(manifold.count_timelines(), Ok(8));
+
+        assert_eq!(manifold.set_tile((0, 3), b'S'), Ok(b'.'));
+        assert_eq!(manifold.sources(), [(0, 3), (0, 7)]);
+        assert_eq!(manifold.set_tile((0, 7), b'.'), Ok(b'S'));
+        assert_eq!(
+            manifold.set_tile((0, 3), b'.'),
+            Err(ManifoldError::NoSource)
+        );
+        assert_eq!(
+            manifold.set_tile((1, 1), b'x'),
+            Err(ManifoldError::InvalidTile {
+                row: 1,
+                column: 1,
+                tile: 'x'
+            })
+        );
+        assert_eq!(
+            manifold.set_tile((16, 0), b'.'),
+            Err(ManifoldError::OutOfBounds { row: 16, column: 0 })
+        );
+        assert_eq!(manifold.tile((16, 0)), None);
+        assert_eq!(manifold.sources(), [(0, 3)]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let crlf = INPUT
+            .split(|c| *c == b'\n')
+            .collect::<Vec<_>>()
+            .join(&b"\r\n"[..]);
+        assert_eq!(
+            TachyonManifold::new(&crlf),
+            Err(ManifoldError::CrlfLineEnding { row: 0 })
+        );
+        assert_eq!(
+            TachyonManifold::new(b"..S..\n.....\r\n"),
+            Err(ManifoldError::CrlfLineEnding { row: 1 })
+        );
+        assert_eq!(
+            TachyonManifold::new(b"..S..\n..\r..\n"),
+            Err(ManifoldError::InvalidTile {
+                row: 1,
+                column: 2,
+                tile: '\r'
+            })
+        );
+        assert_eq!(
+            TachyonManifold::new(b"..S..\n....\n"),
+            Err(ManifoldError::RaggedRow {
+                row: 1,
+                width: 4,
+                expected: 5
+            })
+        );
+        assert_eq!(
+            TachyonManifold::new(b".....\n..^..\n"),
+            Err(ManifoldError::NoSource)
+        );
+        assert_eq!(TachyonManifold::new(b""), Err(ManifoldError::NoSource));
     }
 }
@@ -0,0 +1,311 @@
+use std::{collections::HashMap, fmt::Display};
+
+use super::{
+    ABSORBER, BACK_MIRROR, LEFT_DEFLECTOR, MIRROR, RIGHT_DEFLECTOR, SPLITTER, TachyonManifold,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OpticsConfig {
+    /// Beams leaving one side of the manifold come back from the other side
+    pub wrap_around: bool,
+}
+
+/// Path of the beams through every optical element of the manifold, with positions given
+/// as `(row, column)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trace {
+    splitters_hit: Vec<(usize, usize)>,
+    exits: Vec<((usize, usize), Direction)>,
+    absorbed: Vec<(usize, usize)>,
+    timelines: Result<u64, TraceError>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceError {
+    /// A beam goes through the same position in the same direction twice,
+    /// so it never leaves the manifold
+    Loop {
+        position: (usize, usize),
+        direction: Direction,
+    },
+    /// Number of timelines from the given position no longer fits in an `u64`
+    Overflow { position: (usize, usize) },
+}
+
+/// State of a `(position, direction)` in the depth first search of [`TachyonManifold::trace`]
+enum Visit {
+    InProgress,
+    /// Timelines of the beams leaving from the state
+    Done(u64),
+}
+
+/// Where a beam goes after going through a tile
+enum Step {
+    Enter((usize, usize), Direction),
+    Leave((usize, usize), Direction),
+    Absorbed((usize, usize)),
+}
+
+impl TachyonManifold {
+    /// Follows the beams through splitters `^`, mirrors `/` and `\`, absorbers `#`,
+    /// and one-sided deflectors `<` and `>`
+    ///
+    /// Splitters only split vertical beams, the two beams carrying on diagonally like in
+    /// [`TachyonManifold::count_splits`]; horizontal beams go through them.
+    /// Deflectors send vertical beams toward the side they point to, let beams
+    /// going that way through, and absorb beams hitting their back.
+    pub fn trace(&self, config: &OpticsConfig) -> Trace {
+        let mut exits = vec![];
+        let mut absorbed = vec![];
+        let mut error = None;
+
+        // Depth first search over `(position, direction)`, only keeping the states
+        // that were reached. Each frame adds up the timelines of the states it leads to.
+        let mut visits = HashMap::<((usize, usize), Direction), Visit>::new();
+
+        // Each source sends a beam down into the row under it
+        let mut stack = vec![];
+        let mut sources = self.sources.iter();
+        let mut total_timelines = 0;
+        let mut source_position = (0, 0);
+
+        loop {
+            let Some(((position, direction), steps, next, timelines)) = stack.last_mut() else {
+                let Some(source) = sources.next() else {
+                    break;
+                };
+                source_position = *source;
+                match self.step(*source, Direction::Down, 0, config) {
+                    Step::Enter(position, direction) => match visits.get(&(position, direction)) {
+                        Some(Visit::Done(following)) => add_timelines(
+                            &mut total_timelines,
+                            *following,
+                            source_position,
+                            &mut error,
+                        ),
+                        _ => {
+                            visits.insert((position, direction), Visit::InProgress);
+                            stack.push((
+                                (position, direction),
+                                self.steps(position, direction, config),
+                                0,
+                                0,
+                            ));
+                        }
+                    },
+                    Step::Leave(position, direction) => {
+                        exits.push((position, direction));
+                        add_timelines(&mut total_timelines, 1, source_position, &mut error);
+                    }
+                    Step::Absorbed(_) => unreachable!("Sources never absorb"),
+                }
+                continue;
+            };
+            let current_position = *position;
+
+            let Some(step) = steps.get(*next) else {
+                let count = *timelines;
+                visits.insert((*position, *direction), Visit::Done(count));
+                stack.pop();
+                match stack.last_mut() {
+                    Some(((position, _), _, _, timelines)) => {
+                        add_timelines(timelines, count, *position, &mut error)
+                    }
+                    None => add_timelines(&mut total_timelines, count, source_position, &mut error),
+                }
+                continue;
+            };
+            *next += 1;
+
+            match *step {
+                Step::Enter(position, direction) => match visits.get(&(position, direction)) {
+                    None => {
+                        visits.insert((position, direction), Visit::InProgress);
+                        stack.push((
+                            (position, direction),
+                            self.steps(position, direction, config),
+                            0,
+                            0,
+                        ));
+                    }
+                    Some(Visit::InProgress) => {
+                        error.get_or_insert(TraceError::Loop {
+                            position,
+                            direction,
+                        });
+                    }
+                    Some(Visit::Done(following)) => {
+                        add_timelines(timelines, *following, current_position, &mut error);
+                    }
+                },
+                Step::Leave(position, direction) => {
+                    exits.push((position, direction));
+                    add_timelines(timelines, 1, current_position, &mut error);
+                }
+                Step::Absorbed(position) => {
+                    absorbed.push(position);
+                    add_timelines(timelines, 1, current_position, &mut error);
+                }
+            }
+        }
+
+        let mut splitters_hit = visits
+            .into_keys()
+            .filter(|(position, direction)| {
+                self.manifold[*position] == SPLITTER
+                    && matches!(direction, Direction::Up | Direction::Down)
+            })
+            .map(|(position, _)| position)
+            .collect::<Vec<_>>();
+        splitters_hit.sort_unstable();
+        splitters_hit.dedup();
+        exits.sort_unstable();
+        exits.dedup();
+        absorbed.sort_unstable();
+        absorbed.dedup();
+
+        Trace {
+            splitters_hit,
+            exits,
+            absorbed,
+            timelines: error.map_or(Ok(total_timelines), Err),
+        }
+    }
+
+    /// Where the beam entering `position` going toward `direction` goes next
+    fn steps(
+        &self,
+        position: (usize, usize),
+        direction: Direction,
+        config: &OpticsConfig,
+    ) -> Vec<Step> {
+        let tile = self.manifold[position];
+        let turn = |direction: Direction| vec![self.step(position, direction, 0, config)];
+
+        match (tile, direction) {
+            (SPLITTER, Direction::Up | Direction::Down) => vec![
+                self.step(position, direction, -1, config),
+                self.step(position, direction, 1, config),
+            ],
+            (MIRROR, Direction::Up) | (BACK_MIRROR, Direction::Down) => turn(Direction::Right),
+            (MIRROR, Direction::Down) | (BACK_MIRROR, Direction::Up) => turn(Direction::Left),
+            (MIRROR, Direction::Left) | (BACK_MIRROR, Direction::Right) => turn(Direction::Down),
+            (MIRROR, Direction::Right) | (BACK_MIRROR, Direction::Left) => turn(Direction::Up),
+            (ABSORBER, _)
+            | (LEFT_DEFLECTOR, Direction::Right)
+            | (RIGHT_DEFLECTOR, Direction::Left) => vec![Step::Absorbed(position)],
+            (LEFT_DEFLECTOR, _) => turn(Direction::Left),
+            (RIGHT_DEFLECTOR, _) => turn(Direction::Right),
+            _ => turn(direction),
+        }
+    }
+
+    /// Moves out of `position` toward `direction`, with an extra `column_offset`
+    /// for the diagonal moves of split beams
+    fn step(
+        &self,
+        position: (usize, usize),
+        direction: Direction,
+        column_offset: isize,
+        config: &OpticsConfig,
+    ) -> Step {
+        let (row_offset, direction_offset) = match direction {
+            Direction::Up => (-1, 0),
+            Direction::Down => (1, 0),
+            Direction::Left => (0, -1),
+            Direction::Right => (0, 1),
+        };
+        let width = self.manifold.width() as isize;
+        let column = position.1 as isize + direction_offset + column_offset;
+        let column = if config.wrap_around {
+            column.checked_rem_euclid(width)
+        } else {
+            Some(column).filter(|column| (0..width).contains(column))
+        };
+        let row = position
+            .0
+            .checked_add_signed(row_offset)
+            .filter(|row| *row < self.manifold.height());
+
+        // Beams report the edge they cross, the top or bottom one first
+        // for split beams leaving through a corner
+        match (row, column) {
+            (Some(row), Some(column)) => Step::Enter((row, column as usize), direction),
+            (None, _) => Step::Leave(position, direction),
+            (Some(_), None) if column_offset + direction_offset < 0 => {
+                Step::Leave(position, Direction::Left)
+            }
+            (Some(_), None) => Step::Leave(position, Direction::Right),
+        }
+    }
+}
+
+/// Adds `count` to `timelines`, keeping the first error found
+fn add_timelines(
+    timelines: &mut u64,
+    count: u64,
+    position: (usize, usize),
+    error: &mut Option<TraceError>,
+) {
+    match timelines.checked_add(count) {
+        Some(sum) => *timelines = sum,
+        None => {
+            error.get_or_insert(TraceError::Overflow { position });
+        }
+    }
+}
+
+impl Trace {
+    /// Splitters a vertical beam went through, in reading order
+    pub fn splitters_hit(&self) -> &[(usize, usize)] {
+        &self.splitters_hit
+    }
+
+    /// Last positions of the beams leaving the manifold, with the direction they left toward
+    ///
+    /// Split beams leaving diagonally report the edge they crossed: `Left` or `Right`
+    /// through a side, and their vertical direction through the top, the bottom or a corner.
+    pub fn exits(&self) -> &[((usize, usize), Direction)] {
+        &self.exits
+    }
+
+    /// Positions where beams were absorbed
+    pub fn absorbed(&self) -> &[(usize, usize)] {
+        &self.absorbed
+    }
+
+    /// Number of timelines, each ending when its beam leaves the manifold or is absorbed
+    pub fn timelines(&self) -> Result<u64, TraceError> {
+        self.timelines
+    }
+}
+
+impl Display for TraceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Loop {
+                position: (row, column),
+                direction,
+            } => write!(
+                f,
+                "Beam going {direction:?} loops through row {row}, column {column}."
+            ),
+            Self::Overflow {
+                position: (row, column),
+            } => write!(
+                f,
+                "Number of timelines overflows from row {row}, column {column}."
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TraceError {}
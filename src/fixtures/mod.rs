@@ -0,0 +1,101 @@
+use std::fmt::Write;
+
+/// xorshift64 pseudorandom generator, to get reproducible inputs without dependencies
+#[derive(Debug, Clone)]
+pub struct Xorshift64 {
+    state: u64,
+}
+
+impl Xorshift64 {
+    /// Panics if `seed` is 0, which would only ever generate 0
+    pub fn new(seed: u64) -> Self {
+        assert_ne!(seed, 0, "Seed must not be 0.");
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// Returns `true` with a probability of roughly `percent` percent
+    pub fn chance(&mut self, percent: u64) -> bool {
+        self.next_u64() % 100 < percent
+    }
+}
+
+/// Generates a Day 4 floor plan where roughly `density` percent of the tiles are paper rolls
+pub fn floor_plan(width: usize, height: usize, density: u64, seed: u64) -> Vec<u8> {
+    let mut random = Xorshift64::new(seed);
+    let mut floor_plan = Vec::with_capacity((width + 1) * height);
+    for _ in 0..height {
+        for _ in 0..width {
+            floor_plan.push(if random.chance(density) { b'@' } else { b'.' });
+        }
+        floor_plan.push(b'\n');
+    }
+    floor_plan
+}
+
+/// Generates a Day 5 database of `ranges` ranges, and a list of `items` items to check
+pub fn inventory(ranges: usize, items: usize, seed: u64) -> (String, String) {
+    let mut random = Xorshift64::new(seed);
+
+    let mut database = String::new();
+    for _ in 0..ranges {
+        let start = random.next_u64() % (1 << 48);
+        let length = random.next_u64() % (1 << 24);
+        writeln!(database, "{start}-{}", start + length).unwrap();
+    }
+    let mut list = String::new();
+    for _ in 0..items {
+        writeln!(list, "{}", random.next_u64() % (1 << 48)).unwrap();
+    }
+
+    (database, list)
+}
+
+/// Generates a Day 7 manifold with its source on column `start`, and splitters
+/// on every other line, on roughly `density` percent of the columns
+pub fn sparse_manifold(
+    width: usize,
+    height: usize,
+    start: usize,
+    density: u64,
+    seed: u64,
+) -> Vec<u8> {
+    let mut random = Xorshift64::new(seed);
+    let mut manifold = vec![b'.'; width];
+    manifold[start] = b'S';
+    manifold.push(b'\n');
+    for row in 1..height {
+        for _ in 0..width {
+            let splitter = random.chance(density) && row.is_multiple_of(2);
+            manifold.push(if splitter { b'^' } else { b'.' });
+        }
+        manifold.push(b'\n');
+    }
+    manifold
+}
+
+/// Generates a Day 7 manifold with a full triangle of `rows` rows of splitters, each under
+/// an empty line, so that every split doubles the timelines of its row
+pub fn triangle_manifold(rows: usize) -> Vec<u8> {
+    let width = 2 * rows + 1;
+    let mut manifold = vec![b'.'; width];
+    manifold[rows] = b'S';
+    manifold.push(b'\n');
+    for row in 0..rows {
+        manifold.extend(std::iter::repeat_n(b'.', width));
+        manifold.push(b'\n');
+        let mut line = vec![b'.'; width];
+        for column in ((rows - row)..=(rows + row)).step_by(2) {
+            line[column] = b'^';
+        }
+        manifold.extend(line);
+        manifold.push(b'\n');
+    }
+    manifold
+}
@@ -0,0 +1,387 @@
+use std::{
+    fmt::Display,
+    ops::{Index, IndexMut},
+};
+
+/// Offsets of the 4 orthogonal neighbors, as `(row, column)`
+const ORTHOGONAL: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+/// Offsets of the 8 orthogonal and diagonal neighbors, as `(row, column)`
+const SURROUNDING: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// Row-major 2D grid, indexed by `(row, column)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Creates a grid from cells in row-major order
+    pub fn from_vec(width: usize, height: usize, cells: Vec<T>) -> Self {
+        assert_eq!(
+            width * height,
+            cells.len(),
+            "Cells must fill the whole grid."
+        );
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn contains(&self, (row, column): (usize, usize)) -> bool {
+        row < self.height && column < self.width
+    }
+
+    pub fn get(&self, position: (usize, usize)) -> Option<&T> {
+        self.index_of(position).map(|index| &self.cells[index])
+    }
+
+    pub fn get_mut(&mut self, position: (usize, usize)) -> Option<&mut T> {
+        self.index_of(position).map(|index| &mut self.cells[index])
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[(row * self.width)..((row + 1) * self.width)]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // `chunks` panics on 0 sized chunks, but an empty grid has no rows anyway
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.cells.iter_mut()
+    }
+
+    /// Iterates over all positions of the grid in row-major order
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> + use<T> {
+        let width = self.width;
+        (0..self.height).flat_map(move |row| (0..width).map(move |column| (row, column)))
+    }
+
+    /// Iterates over the cells of the grid alongside their positions
+    pub fn enumerate(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    /// Moves `position` by `(row, column)`, returning `None` if it falls outside of the grid
+    pub fn offset(
+        &self,
+        position: (usize, usize),
+        offset: (isize, isize),
+    ) -> Option<(usize, usize)> {
+        offset_within(self.width, self.height, position, offset)
+    }
+
+    /// Moves `position` by `(row, column)`, wrapping around the edges of the grid,
+    /// returning `None` if the grid has no rows or no columns to wrap around
+    pub fn offset_wrapping(
+        &self,
+        position: (usize, usize),
+        offset: (isize, isize),
+    ) -> Option<(usize, usize)> {
+        offset_wrapping_within(self.width, self.height, position, offset)
+    }
+
+    /// Iterates over the up to 4 orthogonal neighbors of `position` that are inside the grid
+    pub fn neighbors4(
+        &self,
+        position: (usize, usize),
+    ) -> impl Iterator<Item = (usize, usize)> + use<T> {
+        self.neighbors_at(position, &ORTHOGONAL)
+    }
+
+    /// Iterates over the up to 8 orthogonal and diagonal neighbors of `position`
+    /// that are inside the grid
+    pub fn neighbors8(
+        &self,
+        position: (usize, usize),
+    ) -> impl Iterator<Item = (usize, usize)> + use<T> {
+        self.neighbors_at(position, &SURROUNDING)
+    }
+
+    /// Iterates over the neighbors of `position` at each of the `offsets`
+    /// that are inside the grid
+    pub fn neighbors_at<'a>(
+        &self,
+        position: (usize, usize),
+        offsets: &'a [(isize, isize)],
+    ) -> impl Iterator<Item = (usize, usize)> + use<'a, T> {
+        let (width, height) = (self.width, self.height);
+        offsets
+            .iter()
+            .filter_map(move |offset| offset_within(width, height, position, *offset))
+    }
+
+    /// Iterates over the neighbors of `position` at each of the `offsets`,
+    /// wrapping around the edges of the grid, none if the grid is empty
+    pub fn neighbors_wrapping_at<'a>(
+        &self,
+        position: (usize, usize),
+        offsets: &'a [(isize, isize)],
+    ) -> impl Iterator<Item = (usize, usize)> + use<'a, T> {
+        let (width, height) = (self.width, self.height);
+        offsets
+            .iter()
+            .filter_map(move |offset| offset_wrapping_within(width, height, position, *offset))
+    }
+
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.into_iter().map(f).collect(),
+        }
+    }
+
+    fn index_of(&self, position: (usize, usize)) -> Option<usize> {
+        let (row, column) = position;
+        self.contains(position).then_some(row * self.width + column)
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    /// Creates a `width` by `height` grid filled with `value`
+    pub fn new(width: usize, height: usize, value: T) -> Self {
+        Self::from_vec(width, height, vec![value; width * height])
+    }
+
+    /// Creates a grid from a list of rows
+    ///
+    /// # Panics
+    ///
+    /// If the rows do not all have the same length.
+    pub fn from_rows<R: AsRef<[T]>>(rows: impl IntoIterator<Item = R>) -> Self {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = vec![];
+
+        for row in rows {
+            let row = row.as_ref();
+            let expected = *width.get_or_insert(row.len());
+            assert_eq!(
+                expected,
+                row.len(),
+                "Row {height} must have the same length as the first row."
+            );
+            cells.extend_from_slice(row);
+            height += 1;
+        }
+
+        Self::from_vec(width.unwrap_or(0), height, cells)
+    }
+}
+
+impl Grid<u8> {
+    /// Parses a grid of characters separated by `\n`
+    ///
+    /// `\r` at the end of lines and empty lines at the end of the input are ignored,
+    /// other empty lines are rows without any character.
+    ///
+    /// # Panics
+    ///
+    /// If the lines do not all have the same length, see [`Grid::parse_padded`] for those.
+    pub fn parse(input: &[u8]) -> Self {
+        Self::from_rows(Self::lines(input))
+    }
+
+    /// Parses a grid of characters separated by `\n`, padding shorter rows
+    /// with `fill` up to the length of the longest row
+    ///
+    /// `\r` at the end of lines and empty lines at the end of the input are ignored,
+    /// other empty lines are rows made only of `fill`.
+    pub fn parse_padded(input: &[u8], fill: u8) -> Self {
+        let lines = Self::lines(input);
+
+        let width = lines.iter().map(|line| line.len()).max().unwrap_or(0);
+        let mut cells = Vec::with_capacity(width * lines.len());
+        for line in lines.iter() {
+            cells.extend_from_slice(line);
+            cells.resize(cells.len() + width - line.len(), fill);
+        }
+
+        Self::from_vec(width, lines.len(), cells)
+    }
+
+    /// Lines of `input` without their `\r`, dropping the empty lines at the end
+    fn lines(input: &[u8]) -> Vec<&[u8]> {
+        let mut lines = input
+            .split(|c| *c == b'\n')
+            .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+            .collect::<Vec<_>>();
+        while lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+        lines
+    }
+}
+
+impl Display for Grid<u8> {
+    /// Writes each row as a line terminated by `\n`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.rows() {
+            for c in row {
+                write!(f, "{}", char::from(*c))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, position: (usize, usize)) -> &Self::Output {
+        let Some(cell) = self.get(position) else {
+            panic!(
+                "Position {position:?} is outside of {}x{} grid.",
+                self.height, self.width
+            );
+        };
+        cell
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, position: (usize, usize)) -> &mut Self::Output {
+        let (height, width) = (self.height, self.width);
+        let Some(cell) = self.get_mut(position) else {
+            panic!("Position {position:?} is outside of {height}x{width} grid.");
+        };
+        cell
+    }
+}
+
+fn offset_within(
+    width: usize,
+    height: usize,
+    (row, column): (usize, usize),
+    (row_offset, column_offset): (isize, isize),
+) -> Option<(usize, usize)> {
+    let row = row.checked_add_signed(row_offset)?;
+    let column = column.checked_add_signed(column_offset)?;
+    (row < height && column < width).then_some((row, column))
+}
+
+fn offset_wrapping_within(
+    width: usize,
+    height: usize,
+    (row, column): (usize, usize),
+    (row_offset, column_offset): (isize, isize),
+) -> Option<(usize, usize)> {
+    let wrap = |coordinate: usize, offset: isize, length: usize| {
+        let offset = offset.checked_rem_euclid(length as isize)? as usize;
+        Some((coordinate + offset) % length)
+    };
+    Some((
+        wrap(row, row_offset, height)?,
+        wrap(column, column_offset, width)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &[u8] = b"abc\r
+def
+ghi
+
+";
+
+    #[test]
+    fn test_parse() {
+        let grid = Grid::parse(INPUT);
+        assert_eq!(grid.width(), 3);
+        assert_eq!(grid.height(), 3);
+        assert_eq!(grid[(0, 2)], b'c');
+        assert_eq!(grid[(2, 0)], b'g');
+        assert_eq!(grid.row(1), b"def");
+        assert_eq!(grid.get((3, 0)), None);
+        assert_eq!(grid.get((0, 3)), None);
+
+        assert_eq!(grid.to_string(), "abc\ndef\nghi\n");
+
+        let empty = Grid::parse(b"\n");
+        assert!(empty.is_empty());
+        assert_eq!(empty.rows().count(), 0);
+        assert_eq!(empty.offset_wrapping((0, 0), (1, -1)), None);
+        assert_eq!(empty.neighbors_wrapping_at((0, 0), &SURROUNDING).count(), 0);
+        let no_columns = Grid::<u8>::from_rows([[], []]);
+        assert_eq!(no_columns.offset_wrapping((0, 0), (1, 0)), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Row 1 must have the same length as the first row.")]
+    fn test_parse_interior_empty_line() {
+        Grid::parse(b"abc\n\ndef\n");
+    }
+
+    #[test]
+    #[should_panic(expected = "Row 1 must have the same length as the first row.")]
+    fn test_from_ragged_rows() {
+        Grid::from_rows([&b"abc"[..], b"de"]);
+    }
+
+    #[test]
+    fn test_parse_padded() {
+        let grid = Grid::parse_padded(b"ab\r\n\nabcd\nc\n\n", b'.');
+        assert_eq!(grid.width(), 4);
+        assert_eq!(grid.height(), 4);
+        assert_eq!(grid.to_string(), "ab..\n....\nabcd\nc...\n");
+
+        let empty = Grid::parse_padded(b"\n\n", b'.');
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let grid = Grid::parse(INPUT);
+
+        let corner = grid.neighbors4((0, 0)).collect::<Vec<_>>();
+        assert_eq!(corner, [(0, 1), (1, 0)]);
+        let corner = grid.neighbors8((0, 0)).collect::<Vec<_>>();
+        assert_eq!(corner, [(0, 1), (1, 0), (1, 1)]);
+
+        let center = grid
+            .neighbors8((1, 1))
+            .map(|position| grid[position])
+            .collect::<Vec<_>>();
+        assert_eq!(center, b"abcdfghi");
+
+        let edge = grid
+            .neighbors4((2, 1))
+            .map(|position| grid[position])
+            .collect::<Vec<_>>();
+        assert_eq!(edge, b"egi");
+    }
+}
@@ -1,3 +1,4 @@
+pub mod big_uint;
 pub mod day1;
 pub mod day11;
 pub mod day12;
@@ -9,3 +10,9 @@ pub mod day6;
 pub mod day7;
 pub mod day8;
 pub mod day9;
+/// Generated puzzle inputs, shared by the tests and the benchmarks
+#[cfg(any(test, feature = "fixtures"))]
+#[doc(hidden)]
+pub mod fixtures;
+pub mod grid;
+pub mod range_set;
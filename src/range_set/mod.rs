@@ -0,0 +1,355 @@
+use std::{
+    io::{self, Read, Write},
+    ops::RangeInclusive,
+};
+
+/// Header of the binary format of [`RangeSet`]
+const MAGIC: &[u8; 4] = b"RSET";
+
+/// Set of `u64` stored as sorted, disjoint and non touching ranges
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RangeSet {
+    ranges: Vec<RangeInclusive<u64>>,
+}
+
+impl RangeSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Adds `range` to the set, merging it with the ranges it overlaps or touches
+    pub fn insert(&mut self, range: RangeInclusive<u64>) {
+        if range.is_empty() {
+            return;
+        }
+        let (mut start, mut end) = range.into_inner();
+
+        // First range that ends at, or right before, `start`
+        let first = self
+            .ranges
+            .partition_point(|range| range.end().saturating_add(1) < start);
+        // First range that starts after, and not right after, `end`
+        let last = self
+            .ranges
+            .partition_point(|range| end.checked_add(1).is_none_or(|next| *range.start() <= next));
+
+        if first < last {
+            start = start.min(*self.ranges[first].start());
+            end = end.max(*self.ranges[last - 1].end());
+        }
+        self.ranges.splice(first..last, [start..=end]);
+    }
+
+    /// Checks if `value` is in the set in `O(log n)`
+    pub fn contains(&self, value: u64) -> bool {
+        let index = self.ranges.partition_point(|range| *range.end() < value);
+        self.ranges
+            .get(index)
+            .is_some_and(|range| range.contains(&value))
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut ranges = self.ranges.clone();
+        ranges.extend(other.ranges.iter().cloned());
+        ranges.into_iter().collect()
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = vec![];
+        let mut lhs = self.ranges.iter().peekable();
+        let mut rhs = other.ranges.iter().peekable();
+
+        while let (Some(l), Some(r)) = (lhs.peek(), rhs.peek()) {
+            let start = *l.start().max(r.start());
+            let end = *l.end().min(r.end());
+            if start <= end {
+                ranges.push(start..=end);
+            }
+            if l.end() < r.end() {
+                lhs.next();
+            } else {
+                rhs.next();
+            }
+        }
+
+        Self { ranges }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut ranges = vec![];
+        let mut rhs = other.ranges.iter().peekable();
+
+        for range in self.ranges.iter() {
+            let mut start = *range.start();
+            let end = *range.end();
+
+            // Ranges of `other` that end before `range` can't affect later ranges either
+            while rhs.next_if(|r| r.end() < &start).is_some() {}
+
+            let mut exhausted = false;
+            for r in rhs.clone() {
+                if *r.start() > end {
+                    break;
+                }
+                if *r.start() > start {
+                    ranges.push(start..=(r.start() - 1));
+                }
+                match r.end().checked_add(1) {
+                    Some(next) if next <= end => start = next,
+                    _ => {
+                        exhausted = true;
+                        break;
+                    }
+                }
+            }
+            if !exhausted {
+                ranges.push(start..=end);
+            }
+        }
+
+        Self { ranges }
+    }
+
+    /// Number of values in the set, computed from the bounds of each range
+    ///
+    /// Returns an `u128`, as the whole `u64` domain has `u64::MAX + 1` values.
+    pub fn len(&self) -> u128 {
+        self.ranges
+            .iter()
+            .map(|range| u128::from(range.end() - range.start()) + 1)
+            .sum()
+    }
+
+    /// Iterates over the merged ranges, in increasing order
+    pub fn iter(&self) -> impl Iterator<Item = &RangeInclusive<u64>> {
+        self.ranges.iter()
+    }
+
+    /// Writes the set in a compact binary format
+    ///
+    /// The format is the `b"RSET"` header, followed by the number of ranges, then for each range
+    /// the distance from the end of the previous range to its start, and its length minus one,
+    /// all as LEB128 varints.
+    pub fn write_to(&self, mut writer: impl Write) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        write_varint(&mut writer, self.ranges.len() as u64)?;
+
+        let mut previous_end = 0;
+        for range in self.ranges.iter() {
+            write_varint(&mut writer, range.start() - previous_end)?;
+            write_varint(&mut writer, range.end() - range.start())?;
+            previous_end = *range.end();
+        }
+
+        Ok(())
+    }
+
+    /// Reads a set written by [`RangeSet::write_to`]
+    pub fn read_from(mut reader: impl Read) -> io::Result<Self> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("Missing range set header."));
+        }
+
+        let count = read_varint(&mut reader)?;
+        let mut ranges: Vec<RangeInclusive<u64>> = vec![];
+        let mut previous_end = 0u64;
+        for _ in 0..count {
+            let gap = read_varint(&mut reader)?;
+            let length = read_varint(&mut reader)?;
+            // Apart from the first range, ranges must not overlap nor touch
+            if !ranges.is_empty() && gap < 2 {
+                return Err(invalid_data("Ranges must be sorted and disjoint."));
+            }
+            let start = previous_end
+                .checked_add(gap)
+                .ok_or_else(|| invalid_data("Range starts past u64::MAX."))?;
+            let end = start
+                .checked_add(length)
+                .ok_or_else(|| invalid_data("Range ends past u64::MAX."))?;
+            ranges.push(start..=end);
+            previous_end = end;
+        }
+
+        Ok(Self { ranges })
+    }
+}
+
+fn write_varint(writer: &mut impl Write, mut value: u64) -> io::Result<()> {
+    let mut buffer = [0; 10];
+    let mut length = 0;
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            buffer[length] = byte;
+            length += 1;
+            break;
+        }
+        buffer[length] = byte | 0x80;
+        length += 1;
+    }
+    writer.write_all(&buffer[..length])
+}
+
+fn read_varint(reader: &mut impl Read) -> io::Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let mut byte = [0];
+        reader.read_exact(&mut byte)?;
+        let bits = u64::from(byte[0] & 0x7f);
+        if (bits << shift) >> shift != bits {
+            return Err(invalid_data("Varint overflows u64."));
+        }
+        value |= bits << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(invalid_data("Varint overflows u64."))
+}
+
+fn invalid_data(message: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+impl FromIterator<RangeInclusive<u64>> for RangeSet {
+    /// Builds the set in `O(n log n)` by sorting the ranges before merging them
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<u64>>>(iter: I) -> Self {
+        let mut sorted = iter
+            .into_iter()
+            .filter(|range| !range.is_empty())
+            .collect::<Vec<_>>();
+        sorted.sort_unstable_by_key(|range| *range.start());
+
+        let mut ranges: Vec<RangeInclusive<u64>> = Vec::with_capacity(sorted.len());
+        for range in sorted {
+            match ranges.last_mut() {
+                Some(last) if last.end().saturating_add(1) >= *range.start() => {
+                    *last = *last.start()..=*last.end().max(range.end());
+                }
+                _ => ranges.push(range),
+            }
+        }
+
+        Self { ranges }
+    }
+}
+
+impl<'a> IntoIterator for &'a RangeSet {
+    type Item = &'a RangeInclusive<u64>;
+    type IntoIter = std::slice::Iter<'a, RangeInclusive<u64>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.ranges.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(ranges: &[RangeInclusive<u64>]) -> RangeSet {
+        ranges.iter().cloned().collect()
+    }
+
+    #[test]
+    #[expect(clippy::reversed_empty_ranges, reason = "Empty ranges must be ignored")]
+    fn test_merging() {
+        let ranges = set(&[3..=5, 10..=14, 16..=20, 12..=18, 6..=7, 30..=29]);
+        assert_eq!(ranges.iter().collect::<Vec<_>>(), [&(3..=7), &(10..=20)]);
+        assert_eq!(ranges.len(), 16);
+
+        let mut inserted = RangeSet::new();
+        for range in [3..=5, 10..=14, 16..=20, 12..=18, 6..=7, 30..=29] {
+            inserted.insert(range);
+        }
+        assert_eq!(inserted, ranges);
+
+        inserted.insert(0..=100);
+        assert_eq!(inserted, set(&[0..=100]));
+        inserted.insert(102..=103);
+        inserted.insert(101..=101);
+        assert_eq!(inserted, set(&[0..=103]));
+
+        let full = set(&[0..=u64::MAX, 5..=6]);
+        assert_eq!(full.len(), 1 << 64);
+        let mut touching = set(&[(u64::MAX - 1)..=u64::MAX]);
+        touching.insert(0..=(u64::MAX - 2));
+        assert_eq!(touching, full);
+    }
+
+    #[test]
+    fn test_contains() {
+        let ranges = set(&[3..=5, 10..=14, 16..=20, 12..=18]);
+        for (item, fresh) in [
+            (1, false),
+            (3, true),
+            (5, true),
+            (8, false),
+            (11, true),
+            (17, true),
+            (20, true),
+            (21, false),
+            (32, false),
+        ] {
+            assert_eq!(ranges.contains(item), fresh, "{item}");
+        }
+        assert!(!RangeSet::new().contains(0));
+        assert!(set(&[0..=u64::MAX]).contains(u64::MAX));
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let lhs = set(&[0..=10, 20..=30, 40..=50]);
+        let rhs = set(&[5..=25, 28..=28, 45..=u64::MAX]);
+
+        assert_eq!(lhs.union(&rhs), set(&[0..=30, 40..=u64::MAX]));
+        assert_eq!(
+            lhs.intersection(&rhs),
+            set(&[5..=10, 20..=25, 28..=28, 45..=50])
+        );
+        assert_eq!(
+            lhs.difference(&rhs),
+            set(&[0..=4, 26..=27, 29..=30, 40..=44])
+        );
+        assert_eq!(rhs.difference(&lhs), set(&[11..=19, 51..=u64::MAX]));
+
+        assert_eq!(lhs.difference(&lhs), RangeSet::new());
+        assert_eq!(lhs.intersection(&RangeSet::new()), RangeSet::new());
+        assert_eq!(lhs.difference(&RangeSet::new()), lhs);
+    }
+
+    #[test]
+    fn test_serialization() {
+        for ranges in [
+            RangeSet::new(),
+            set(&[3..=5, 10..=14, 16..=20, 12..=18]),
+            set(&[0..=0, 2..=2, 1000..=1_000_000, (u64::MAX - 5)..=u64::MAX]),
+            set(&[0..=u64::MAX]),
+        ] {
+            let mut buffer = vec![];
+            ranges.write_to(&mut buffer).unwrap();
+            assert_eq!(RangeSet::read_from(buffer.as_slice()).unwrap(), ranges);
+        }
+
+        let mut buffer = vec![];
+        set(&[3..=5, 10..=20]).write_to(&mut buffer).unwrap();
+        assert_eq!(buffer, b"RSET\x02\x03\x02\x05\x0a");
+
+        for corrupted in [
+            &b"RSEX\x00"[..],
+            b"RSET\x02\x03\x02\x05",
+            b"RSET\x02\x03\x02\x01\x0a",
+            b"RSET\x01\xff\xff\xff\xff\xff\xff\xff\xff\xff\x7f\x00",
+            b"RSET\x02\x00\xff\xff\xff\xff\xff\xff\xff\xff\xff\x01\x02\x00",
+        ] {
+            assert!(RangeSet::read_from(corrupted).is_err(), "{corrupted:?}");
+        }
+    }
+}